@@ -0,0 +1,91 @@
+//! Compares looking at the neighbours of every cell with the cells stored in
+//! nested columns, as `Board` used to keep them, and in its flat `Vec`. Run
+//! with `cargo bench`.
+
+use rusty_minesweeper::game::models::board::Board;
+use rusty_minesweeper::game::models::cell::{Cell, CellPosition};
+use rusty_minesweeper::game::models::game::GameDifficulty;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 2000;
+
+fn main() {
+    let board = Board::new(
+        GameDifficulty::EXPERT,
+        CellPosition::new(0, 0),
+        None,
+        Some(1),
+    )
+    .expect("The benchmark board is valid");
+    let columns = nested_columns(&board);
+
+    let old = measure(|| count_nested(&board, &columns));
+    let new = measure(|| count_flat(&board));
+    assert_eq!(
+        count_nested(&board, &columns),
+        count_flat(&board),
+        "Both layouts see the same mines"
+    );
+
+    println!("adjacency, {ITERATIONS} passes over a 30x16 board");
+    println!("  nested Vec<Vec<Cell>>: {:?} per pass", old / ITERATIONS);
+    println!("  flat Vec<Cell>:        {:?} per pass", new / ITERATIONS);
+}
+
+/// Copies the cells of `board` into one `Vec` per column.
+fn nested_columns(board: &Board) -> Vec<Vec<Cell>> {
+    let (width, height) = board.size();
+
+    (0..width)
+        .map(|x| {
+            (0..height)
+                .map(|y| *board.cell(CellPosition::new(x, y)).expect("In bounds"))
+                .collect()
+        })
+        .collect()
+}
+
+fn measure(mut pass: impl FnMut() -> usize) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(pass());
+    }
+
+    start.elapsed()
+}
+
+/// Counts the mines next to every cell, reading the neighbours from the
+/// nested columns.
+fn count_nested(board: &Board, columns: &[Vec<Cell>]) -> usize {
+    let (width, height) = board.size();
+    let mut total = 0;
+
+    for x in 0..width {
+        for y in 0..height {
+            total += board
+                .adjacent_positions(CellPosition::new(x, y))
+                .filter(|adj_pos| columns[adj_pos.x][adj_pos.y].is_mine())
+                .count();
+        }
+    }
+
+    total
+}
+
+/// Counts the mines next to every cell, reading the neighbours from the board.
+fn count_flat(board: &Board) -> usize {
+    let (width, height) = board.size();
+    let mut total = 0;
+
+    for x in 0..width {
+        for y in 0..height {
+            total += board
+                .adjacent_positions(CellPosition::new(x, y))
+                .filter(|adj_pos| board.cell(*adj_pos).is_ok_and(|cell| cell.is_mine()))
+                .count();
+        }
+    }
+
+    total
+}
@@ -0,0 +1,67 @@
+//! Times the flood fill of `GameState::reveal_cell` opening a large area.
+//! Run with `cargo bench`.
+//!
+//! The board is a seeded 30x16 expert board with no mines around the center,
+//! so the first click opens an area instead of a lone number.
+
+use rusty_minesweeper::game::models::board::{Board, RevealResult};
+use rusty_minesweeper::game::models::cell::CellPosition;
+use rusty_minesweeper::game::models::game::GameDifficulty;
+use rusty_minesweeper::game::state::GameState;
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// Of the first few thousand seeds, the one with the largest opening
+const SEED: u64 = 4200;
+const ITERATIONS: u32 = 2000;
+
+fn main() {
+    let start = CellPosition::new(15, 8);
+    let board = seeded_board(start);
+    let mut game_state =
+        GameState::new(GameDifficulty::EXPERT).expect("The benchmark difficulty is valid");
+
+    let (elapsed, opening_size) = measure(&mut game_state, &board, start);
+
+    println!("flood fill, {ITERATIONS} reveals of a {opening_size} cell opening on a 30x16 board");
+    println!(
+        "  GameState::reveal_cell: {:?} per reveal",
+        elapsed / ITERATIONS
+    );
+}
+
+/// Builds the expert board of `SEED`, keeping the mines off `start` and its
+/// neighbors.
+fn seeded_board(start: CellPosition) -> Board {
+    let (width, height) = GameDifficulty::EXPERT.board_size;
+    let protected: HashSet<CellPosition> = (start.x - 1..=start.x + 1)
+        .flat_map(|x| (start.y - 1..=start.y + 1).map(move |y| CellPosition::new(x, y)))
+        .filter(|pos| pos.x < width && pos.y < height)
+        .collect();
+
+    Board::new(GameDifficulty::EXPERT, start, Some(&protected), Some(SEED))
+        .expect("The benchmark board is valid")
+}
+
+/// Times `ITERATIONS` reveals of `start`, each on a fresh copy of `board`.
+/// Loading the board is left out of the time.
+fn measure(game_state: &mut GameState, board: &Board, start: CellPosition) -> (Duration, usize) {
+    let mut total = Duration::ZERO;
+    let mut opening_size = 0;
+
+    for _ in 0..ITERATIONS {
+        game_state
+            .load_board(board.clone())
+            .expect("The benchmark board loads");
+        let begin = Instant::now();
+        let result = black_box(game_state.reveal_cell(start));
+        total += begin.elapsed();
+
+        if let Ok(RevealResult::Continue(count)) = result {
+            opening_size = count;
+        }
+    }
+
+    (total, opening_size)
+}
@@ -0,0 +1,68 @@
+//! Compares switching the cell grid between Beginner and Expert by
+//! rebuilding every cell, as `App` used to, and with `resize_grid`. Run with
+//! `cargo bench`; it needs a display for GTK.
+
+use relm4::prelude::FactoryVecDeque;
+use rusty_minesweeper::components::button_cell::{resize_grid, ButtonCell};
+use rusty_minesweeper::game::models::cell::CellPosition;
+use rusty_minesweeper::game::models::game::GameDifficulty;
+use std::time::{Duration, Instant};
+
+const CELL_SIZE: i32 = 24;
+const ITERATIONS: u32 = 50;
+
+fn main() {
+    if gtk::init().is_err() {
+        eprintln!("resize: GTK could not be initialized, is a display available?");
+        return;
+    }
+
+    let sizes = [
+        GameDifficulty::BEGINNER.board_size,
+        GameDifficulty::EXPERT.board_size,
+    ];
+    let mut cells: FactoryVecDeque<ButtonCell> =
+        FactoryVecDeque::builder().launch_default().detach();
+
+    let old = measure(&mut cells, &sizes, rebuild_grid);
+    let new = measure(&mut cells, &sizes, |cells, board_size| {
+        resize_grid(cells, board_size, CELL_SIZE);
+    });
+
+    println!("resize, {ITERATIONS} Beginner<->Expert round trips");
+    println!(
+        "  rebuild every cell: {:?} per switch",
+        old / (2 * ITERATIONS)
+    );
+    println!(
+        "  resize_grid:        {:?} per switch",
+        new / (2 * ITERATIONS)
+    );
+}
+
+fn measure(
+    cells: &mut FactoryVecDeque<ButtonCell>,
+    sizes: &[(usize, usize)],
+    mut switch: impl FnMut(&mut FactoryVecDeque<ButtonCell>, (usize, usize)),
+) -> Duration {
+    rebuild_grid(cells, sizes[0]);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for board_size in sizes.iter().rev() {
+            switch(cells, *board_size);
+        }
+    }
+
+    start.elapsed()
+}
+
+/// Drops every cell and adds a new one for each position of `board_size`.
+fn rebuild_grid(cells: &mut FactoryVecDeque<ButtonCell>, board_size: (usize, usize)) {
+    let mut cells_guard = cells.guard();
+    cells_guard.clear();
+    for index in 0..board_size.0 * board_size.1 {
+        let position = CellPosition::from_index(index, board_size);
+        cells_guard.push_back(ButtonCell::new(position, CELL_SIZE));
+    }
+}
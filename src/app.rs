@@ -1,24 +1,67 @@
-use crate::components::button_cell::{ButtonCell, ButtonMsg};
-use crate::game::models::board::RevealResult;
-use crate::game::models::cell::CellPosition;
-use crate::game::models::game::GameDifficulty;
-use crate::game::state::GameState;
+use crate::audio::SoundPlayer;
+use crate::components::button_cell::{resize_grid, ButtonCell, ButtonMsg};
+use crate::error::{GameError, GameResult};
+use crate::game::models::board::{Board, CantReveal, RevealResult};
+use crate::game::models::cell::{CellContent, CellPosition, Mark};
+use crate::game::models::game::{GameDifficulty, GameStatus, Preset};
+use crate::game::models::options::{GameOptions, MarkCycle};
+use crate::game::models::summary::ScoreCategory;
+use crate::game::replay::{Replay, ReplayAction, ReplayEvent};
+use crate::game::scores::{BestTimes, Statistics};
+use crate::game::solver::{AutoSolveOutcome, Move, Rating, Solver};
+use crate::game::state::{GameState, REVEALED_CELL_CLASS};
+use crate::settings::Settings;
+use crate::theme::{NumberPalette, Theme, ThemeProvider};
 use gtk::gdk_pixbuf::Pixbuf;
 use gtk::glib::ControlFlow;
 use gtk::{gdk, prelude::*};
 use relm4::actions::{RelmAction, RelmActionGroup};
 use relm4::prelude::FactoryVecDeque;
 use relm4::{ComponentParts, RelmWidgetExt, SimpleComponent};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 const APP_ICON: &[u8] = include_bytes!("../assets/logo.png");
-const REVEALED_CELL_CLASS: &str = "revealed-cell";
 const LOST_CELL_CLASS: &str = "lost-cell";
-const EMPTY_STRING: String = String::new();
+const JUST_REVEALED_CLASS: &str = "just-revealed";
+const WRONG_FLAG_CLASS: &str = "wrong-flag";
+const WRONG_FLAG_LABEL: &str = "❌";
+const PATTERN_HINT_CLASS: &str = "pattern-hint";
+const UNSAFE_CHORD_CLASS: &str = "unsafe-chord";
+const HINT_CLASS: &str = "hint-cell";
+const HIDE_FLAGS_KEY: gdk::Key = gdk::Key::h;
+const FLAG_KEY: gdk::Key = gdk::Key::f;
+const QUESTION_KEY: gdk::Key = gdk::Key::q;
+const SMART_CHORD_KEY: gdk::Key = gdk::Key::s;
+const SWEEP_KEY: gdk::Key = gdk::Key::w;
+const REVEAL_KEY: gdk::Key = gdk::Key::Return;
+const REVEAL_SPACE_KEY: gdk::Key = gdk::Key::space;
+const REVEAL_WAVE_INTERVAL: Duration = Duration::from_millis(15);
+const REVEAL_HIGHLIGHT_DURATION: Duration = Duration::from_millis(800);
+const DEMO_MOVE_INTERVAL: Duration = Duration::from_millis(250);
+const LUCKY_TOAST_DURATION: Duration = Duration::from_secs(2);
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(4);
+const HINT_PULSE_DURATION: Duration = Duration::from_millis(1200);
+// Room for the board when the cell size is picked automatically
+const AUTO_FIT_BOARD_SIZE: (usize, usize) = (640, 480);
+// Cell sizes zooming goes through, in pixels.
+const ZOOM_MIN_CELL_SIZE: i32 = 12;
+const ZOOM_MAX_CELL_SIZE: i32 = 48;
+const ZOOM_STEP: i32 = 4;
+// Largest width and height the custom difficulty dialog offers.
+const CUSTOM_MAX_SIDE: u32 = 100;
+const CONFIG_DIR_NAME: &str = "rusty-minesweeper";
+const BEST_TIMES_FILE: &str = "best_times.json";
+const STATISTICS_FILE: &str = "statistics.json";
+const SETTINGS_FILE: &str = "settings.json";
+const SAVED_GAME_NAME: &str = "minesweeper-game.json";
+const SAVED_REPLAY_NAME: &str = "minesweeper-replay.json";
 
 relm4::new_action_group!(WindowActionGroup, "win");
 relm4::new_stateless_action!(NewGameAction, WindowActionGroup, "new-game");
+relm4::new_stateless_action!(RetryAction, WindowActionGroup, "retry");
 relm4::new_stateless_action!(
     SetDifficultyBeginnerAction,
     WindowActionGroup,
@@ -39,27 +82,270 @@ relm4::new_stateless_action!(
     WindowActionGroup,
     "difficulty-custom"
 );
+relm4::new_stateful_action!(
+    SetDifficultyLevelAction,
+    WindowActionGroup,
+    "difficulty-level",
+    u8,
+    ()
+);
+relm4::new_stateful_action!(SoundAction, WindowActionGroup, "sound", (), bool);
+relm4::new_stateful_action!(
+    AutoChordOnFlagAction,
+    WindowActionGroup,
+    "auto-chord-on-flag",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    NumberGlyphsAction,
+    WindowActionGroup,
+    "number-glyphs",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    RevealAnimationAction,
+    WindowActionGroup,
+    "reveal-animation",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    RevealHighlightAction,
+    WindowActionGroup,
+    "reveal-highlight",
+    (),
+    bool
+);
+relm4::new_stateful_action!(AutoPauseAction, WindowActionGroup, "auto-pause", (), bool);
+relm4::new_stateful_action!(SmartClickAction, WindowActionGroup, "smart-click", (), bool);
+relm4::new_stateful_action!(
+    VerboseAnnouncementsAction,
+    WindowActionGroup,
+    "verbose-announcements",
+    (),
+    bool
+);
+relm4::new_stateful_action!(StartHintAction, WindowActionGroup, "start-hint", (), bool);
+relm4::new_stateful_action!(
+    WorriedFaceAction,
+    WindowActionGroup,
+    "worried-face",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    OpenAllZerosAction,
+    WindowActionGroup,
+    "open-all-zeros",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    FirstClickOpeningAction,
+    WindowActionGroup,
+    "first-click-opening",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    ResultDialogAction,
+    WindowActionGroup,
+    "result-dialog",
+    (),
+    bool
+);
+relm4::new_stateful_action!(CellSizeAction, WindowActionGroup, "cell-size", i32, i32);
+relm4::new_stateless_action!(ZoomInAction, WindowActionGroup, "zoom-in");
+relm4::new_stateless_action!(ZoomOutAction, WindowActionGroup, "zoom-out");
+relm4::new_stateful_action!(ThemeAction, WindowActionGroup, "theme", u8, u8);
+relm4::new_stateful_action!(
+    NumberPaletteAction,
+    WindowActionGroup,
+    "number-palette",
+    u8,
+    u8
+);
+relm4::new_stateful_action!(
+    TimerHeadStartAction,
+    WindowActionGroup,
+    "timer-head-start",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    PatternHintsAction,
+    WindowActionGroup,
+    "pattern-hints",
+    (),
+    bool
+);
+relm4::new_stateful_action!(MineOddsAction, WindowActionGroup, "mine-odds", (), bool);
+relm4::new_stateful_action!(
+    SafeCellsBadgeAction,
+    WindowActionGroup,
+    "safe-cells-badge",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    GuardUnsafeChordsAction,
+    WindowActionGroup,
+    "guard-unsafe-chords",
+    (),
+    bool
+);
+relm4::new_stateful_action!(
+    QuestionMarksAction,
+    WindowActionGroup,
+    "question-marks",
+    (),
+    bool
+);
 relm4::new_stateless_action!(ExitAction, WindowActionGroup, "exit");
+relm4::new_stateless_action!(DemoAction, WindowActionGroup, "demo");
+relm4::new_stateless_action!(AutoSolveAction, WindowActionGroup, "auto-solve");
+relm4::new_stateless_action!(CopyResultAction, WindowActionGroup, "copy-result");
+relm4::new_stateless_action!(CopyBoardAction, WindowActionGroup, "copy-board");
+relm4::new_stateless_action!(UndoAction, WindowActionGroup, "undo");
+relm4::new_stateless_action!(HintAction, WindowActionGroup, "hint");
+relm4::new_stateless_action!(PauseAction, WindowActionGroup, "pause");
+relm4::new_stateless_action!(
+    NewGameFromSeedAction,
+    WindowActionGroup,
+    "new-game-from-seed"
+);
+relm4::new_stateless_action!(SaveGameAction, WindowActionGroup, "save-game");
+relm4::new_stateless_action!(LoadGameAction, WindowActionGroup, "load-game");
+relm4::new_stateless_action!(
+    LoadBoardFromTextAction,
+    WindowActionGroup,
+    "load-board-from-text"
+);
+relm4::new_stateless_action!(SaveReplayAction, WindowActionGroup, "save-replay");
+relm4::new_stateless_action!(WatchReplayAction, WindowActionGroup, "watch-replay");
+relm4::new_stateless_action!(StatisticsAction, WindowActionGroup, "statistics");
 relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
 
 pub struct App {
     game_state: GameState,
     mouse_tracker: MouseTracker,
     cells: FactoryVecDeque<ButtonCell>,
+    sound: SoundPlayer,
+    flags_hidden: bool,
+    number_glyphs: bool,
+    reveal_animation: RevealAnimation,
+    reveal_highlight: RevealHighlight,
+    auto_pause: bool,
+    start_hint: bool,
+    smart_click: bool,
+    verbose_announcements: bool,
+    /// What screen readers were last told about a keyboard reveal.
+    announcement: String,
+    /// Cell the keyboard acts on, moved with the arrow keys.
+    focused_cell: CellPosition,
+    /// Index of the cell the latest hint revealed, pulsing for a moment.
+    hint_cell: Option<usize>,
+    /// Whether the player paused the game, rather than the window losing
+    /// focus. Only the player resumes it then.
+    paused_by_player: bool,
+    /// Plays the solver's moves one at a time, for the demo or auto-solve.
+    demo_timer: Option<gtk::glib::SourceId>,
+    worried_face: bool,
+    pattern_hints: bool,
+    pattern_cells: Vec<usize>,
+    mine_odds: bool,
+    /// Odds shown on each hidden cell, by cell index.
+    mine_odds_cells: HashMap<usize, String>,
+    safe_cells_badge: bool,
+    unsafe_chord_cell: Option<usize>,
+    complexity: Option<Rating>,
+    drawn_generation: u64,
+    lucky_toast: LuckyToast,
+    status_message: StatusMessage,
+    clock: Clock,
+    end_stats: EndStats,
+    /// Games played over time, saved next to the best times.
+    statistics: Statistics,
+    replay: ReplayPlayback,
+    /// Preferences kept between launches, saved as soon as they change.
+    settings: Settings,
+    theme_provider: ThemeProvider,
 }
 
 #[derive(Debug)]
 pub enum Msg {
     Restart,
-    ChangeDifficulty(GameDifficulty),
+    Retry,
+    CloseEndStats,
+    FaceClicked,
+    TogglePause,
+    RestartWithSeed(u64),
+    ShowNewGameFromSeed,
+    Undo,
+    Hint,
+    ClearHint(usize),
+    Quit,
+    ChangeDifficulty(Preset),
+    ShowCustomDifficulty,
     ShowAbout,
-    Tick,
+    ShowStatistics,
+    ToggleSound(bool),
+    ToggleAutoChordOnFlag(bool),
+    ToggleNumberGlyphs(bool),
+    ToggleRevealAnimation(bool),
+    RevealWave,
+    ToggleRevealHighlight(bool),
+    ClearRevealHighlight(u64),
+    ToggleAutoPause(bool),
+    WindowActiveChanged(bool),
+    ToggleStartHint(bool),
+    ToggleSmartClick(bool),
+    ToggleVerboseAnnouncements(bool),
+    ToggleWorriedFace(bool),
+    ToggleOpenAllZeros(bool),
+    ToggleFirstClickOpening(bool),
+    ToggleResultDialog(bool),
+    SetCellSize(Option<i32>),
+    Zoom(i32),
+    SetTheme(Theme),
+    SetNumberPalette(NumberPalette),
+    ToggleTimerHeadStart(bool),
+    ToggleGuardUnsafeChords(bool),
+    ToggleQuestionMarks(bool),
+    TogglePatternHints(bool),
+    ToggleMineOdds(bool),
+    ToggleSafeCellsBadge(bool),
+    CopySeed,
+    CopyResult,
+    CopyBoard,
+    ShowSaveGame,
+    ShowLoadGame,
+    SaveGame(PathBuf),
+    LoadGame(PathBuf),
+    ShowLoadBoardFromText,
+    LoadBoardFromText(String),
+    ShowSaveReplay,
+    ShowWatchReplay,
+    SaveReplay(PathBuf),
+    WatchReplay(PathBuf),
+    ReplayStep(u64),
+    HideLuckyToast,
+    ClearStatusMessage(u64),
+    StartDemo,
+    AutoSolve,
+    DemoMove,
+    Tick(u64),
     TrackMouse(f64, f64),
     LeftButtonPressed,
     LeftButtonReleased,
     RightButtonPressed,
     MiddleButtonPressed,
     MiddleButtonReleased,
+    KeyPressed(gdk::Key),
+    MoveFocus(isize, isize),
+    KeyReleased(gdk::Key),
 }
 
 #[relm4::component(pub)]
@@ -72,6 +358,45 @@ impl SimpleComponent for App {
         main_window = gtk::Window {
             set_title: Some("Rusty Minesweeper"),
             set_resizable: false,
+            connect_is_active_notify[sender] => move |window| {
+                sender.input(Msg::WindowActiveChanged(window.is_active()));
+            },
+            connect_close_request[sender] => move |_| {
+                sender.input(Msg::Quit);
+                gtk::glib::Propagation::Stop
+            },
+            add_controller = gtk::EventControllerKey {
+                connect_key_pressed[sender] => move |_, key, _, _| {
+                    sender.input(Msg::KeyPressed(key));
+                    gtk::glib::Propagation::Proceed
+                },
+                connect_key_released[sender] => move |_, key, _, _| {
+                    sender.input(Msg::KeyReleased(key));
+                },
+            },
+            // Arrow keys move the GTK focus on their own, so they are caught
+            // before the focused widget gets them
+            add_controller = gtk::EventControllerKey {
+                set_propagation_phase: gtk::PropagationPhase::Capture,
+                connect_key_pressed[sender] => move |controller, key, _, _| {
+                    let Some((dx, dy)) = arrow_direction(key) else {
+                        return gtk::glib::Propagation::Proceed;
+                    };
+                    // The menu bar keeps its own arrow key navigation
+                    let in_menu_bar = controller
+                        .widget()
+                        .and_then(|window| window.root()?.focus())
+                        .is_some_and(|focus| {
+                            focus.ancestor(gtk::PopoverMenuBar::static_type()).is_some()
+                        });
+                    if in_menu_bar {
+                        return gtk::glib::Propagation::Proceed;
+                    }
+
+                    sender.input(Msg::MoveFocus(dx, dy));
+                    gtk::glib::Propagation::Stop
+                },
+            },
 
             gtk::Box {
                 set_css_classes: &["main-box"],
@@ -104,15 +429,32 @@ impl SimpleComponent for App {
                             set_label: &format!("{:03}", model.game_state.flags_remaining())
                         },
 
-                        #[name(restart_button)]
-                        gtk::Button {
+                        gtk::Overlay {
                             set_halign: gtk::Align::Center,
-                            set_size_request: (10, 10),
-                            add_css_class: "restart_button",
-                            #[watch]
-                            set_label: &model.emoji_status(),
-                            connect_clicked[sender] => move |_| {
-                                sender.input(Msg::Restart);
+
+                            #[wrap(Some)]
+                            #[name(restart_button)]
+                            set_child = &gtk::Button {
+                                set_size_request: (10, 10),
+                                add_css_class: "restart_button",
+                                #[watch]
+                                set_label: &model.emoji_status(),
+                                connect_clicked[sender] => move |_| {
+                                    sender.input(Msg::FaceClicked);
+                                },
+                            },
+
+                            add_overlay = &gtk::Label {
+                                set_css_classes: &["safe-cells-badge"],
+                                set_halign: gtk::Align::End,
+                                set_valign: gtk::Align::Start,
+                                // Lets clicks through to the button below
+                                set_can_target: false,
+                                #[watch]
+                                set_label: &model.game_state.remaining_safe_cells().to_string(),
+                                #[watch]
+                                set_visible: model.safe_cells_badge
+                                    && !model.game_state.status().is_over(),
                             },
                         },
 
@@ -122,46 +464,171 @@ impl SimpleComponent for App {
                             set_halign: gtk::Align::End,
                             set_css_classes: &["time_remaining_label", "seven-segment"],
                             #[watch]
-                            set_label: &format!("{:03}", model.game_state.elapsed_seconds())
+                            set_label: &format!("{:03}", model.game_state.elapsed_seconds()),
+                            // Dimmed until the first click starts the timer
+                            #[watch]
+                            set_class_active: (
+                                "timer-idle",
+                                model.start_hint && model.game_state.status().is_new(),
+                            ),
                         },
                     },
 
-                    gtk::Box {
-                        set_css_classes: &["bottom-box"],
-                        #[local_ref]
-                        cells_grid -> gtk::Grid {
-                            set_row_homogeneous: true,
-                            set_column_homogeneous: true,
-                            add_controller = gtk::EventControllerMotion {
-                                connect_motion[sender]=> move |_, x, y| {
-                                    sender.input(Msg::TrackMouse(x, y));
-                                }
-                            },
-                            add_controller = gtk::GestureClick {
-                                connect_pressed[sender] => move |_, _, _, _|{
-                                    sender.input(Msg::LeftButtonPressed);
+                    gtk::Overlay {
+                        #[wrap(Some)]
+                        set_child = &gtk::Box {
+                            set_css_classes: &["bottom-box"],
+                            #[local_ref]
+                            cells_grid -> gtk::Grid {
+                                set_row_homogeneous: true,
+                                set_column_homogeneous: true,
+                                add_controller = gtk::EventControllerMotion {
+                                    connect_motion[sender]=> move |_, x, y| {
+                                        sender.input(Msg::TrackMouse(x, y));
+                                    }
                                 },
-                                connect_released[sender] => move |_, _, _, _|{
-                                    sender.input(Msg::LeftButtonReleased);
+                                add_controller = gtk::GestureClick {
+                                    connect_pressed[sender] => move |_, _, _, _|{
+                                        sender.input(Msg::LeftButtonPressed);
+                                    },
+                                    connect_released[sender] => move |_, _, _, _|{
+                                        sender.input(Msg::LeftButtonReleased);
+                                    },
                                 },
-                            },
+                                add_controller = gtk::GestureClick {
+                                    set_button: gtk::gdk::ffi::GDK_BUTTON_SECONDARY as u32,
+                                    connect_begin[sender] => move |_, _|{
+                                        sender.input(Msg::RightButtonPressed);
+                                    },
+                                },
+                                add_controller = gtk::GestureClick {
+                                    set_button: gtk::gdk::ffi::GDK_BUTTON_MIDDLE as u32,
+                                    connect_pressed[sender] => move |_, _, _, _|{
+                                        sender.input(Msg::MiddleButtonPressed);
+                                    },
+                                    connect_released[sender] => move |_, _, _, _|{
+                                        sender.input(Msg::MiddleButtonReleased);
+                                    },
+                                },
+                            }
+                        },
+
+                        add_overlay = &gtk::Label {
+                            set_label: "Click to start",
+                            set_css_classes: &["start-hint"],
+                            set_halign: gtk::Align::Center,
+                            set_valign: gtk::Align::Center,
+                            // Lets clicks through to the cells below
+                            set_can_target: false,
+                            #[watch]
+                            set_visible: model.start_hint && model.game_state.status().is_new(),
+                        },
+
+                        // Covers the board so it can't be studied while the
+                        // timer is stopped, and keeps clicks off the cells
+                        add_overlay = &gtk::Label {
+                            set_label: "Paused",
+                            set_css_classes: &["paused-overlay"],
+                            #[watch]
+                            set_visible: model.game_state.is_paused(),
+                        },
+
+                        add_overlay = &gtk::Label {
+                            set_label: "Lucky start!",
+                            set_css_classes: &["lucky-toast"],
+                            set_halign: gtk::Align::Center,
+                            set_valign: gtk::Align::Start,
+                            set_can_target: false,
+                            #[watch]
+                            set_visible: model.lucky_toast.visible,
+                        },
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+
+                        gtk::Label {
+                            set_hexpand: true,
+                            set_halign: gtk::Align::Start,
+                            set_css_classes: &["status-label"],
+                            #[watch]
+                            set_label: &model.complexity.map_or_else(
+                                || "Complexity: -".to_string(),
+                                |rating| format!("Complexity: {rating}"),
+                            ),
+                        },
+
+                        // Screen readers read it out whenever it changes
+                        gtk::Label::builder().accessible_role(gtk::AccessibleRole::Status).build() {
+                            set_css_classes: &["status-label"],
+                            #[watch]
+                            set_label: &model.announcement,
+                        },
+
+                        gtk::Label {
+                            set_hexpand: true,
+                            set_css_classes: &["status-label"],
+                            #[watch]
+                            set_class_active: ("status-error", model.status_message.error),
+                            #[watch]
+                            set_label: model.status_message.text.as_deref().unwrap_or_default(),
+                        },
+
+                        gtk::Label {
+                            set_halign: gtk::Align::End,
+                            set_css_classes: &["status-label"],
+                            set_tooltip_text: Some("Click to copy the seed"),
+                            #[watch]
+                            set_label: &model.game_state.seed().map_or_else(
+                                || "Seed: -".to_string(),
+                                |seed| format!("Seed: {seed}"),
+                            ),
                             add_controller = gtk::GestureClick {
-                                set_button: gtk::gdk::ffi::GDK_BUTTON_SECONDARY as u32,
-                                connect_begin[sender] => move |_, _|{
-                                    sender.input(Msg::RightButtonPressed);
+                                connect_released[sender] => move |_, _, _, _| {
+                                    sender.input(Msg::CopySeed);
                                 },
                             },
-                            add_controller = gtk::GestureClick {
-                                set_button: gtk::gdk::ffi::GDK_BUTTON_MIDDLE as u32,
-                                connect_pressed[sender] => move |_, _, _, _|{
-                                    sender.input(Msg::MiddleButtonPressed);
+                        },
+
+                        gtk::MenuButton {
+                            set_label: "Game Stats",
+                            set_css_classes: &["status-label"],
+                            #[watch]
+                            set_sensitive: model.end_stats.three_bv.is_some(),
+                            #[watch]
+                            set_active: model.end_stats.open,
+                            #[wrap(Some)]
+                            set_popover = &gtk::Popover {
+                                connect_closed[sender] => move |_| {
+                                    sender.input(Msg::CloseEndStats);
                                 },
-                                connect_released[sender] => move |_, _, _, _|{
-                                    sender.input(Msg::MiddleButtonReleased);
+
+                                gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_spacing: 5,
+                                    set_margin_all: 5,
+
+                                    gtk::Label {
+                                        set_halign: gtk::Align::Start,
+                                        #[watch]
+                                        set_label: &model.end_stats.three_bv.map_or_else(
+                                            || "3BV: -".to_string(),
+                                            |three_bv| format!("3BV: {three_bv}"),
+                                        ),
+                                    },
+
+                                    gtk::Label {
+                                        set_halign: gtk::Align::Start,
+                                        #[watch]
+                                        set_label: &model.end_stats.three_bv_per_second.map_or_else(
+                                            || "3BV/s: -".to_string(),
+                                            |speed| format!("3BV/s: {speed:.2}"),
+                                        ),
+                                    },
                                 },
                             },
-                        }
-                    }
+                        },
+                    },
                 }
             }
         }
@@ -170,22 +637,96 @@ impl SimpleComponent for App {
     menu! {
         main_menu: {
             custom: "menubar",
-            "Game" {
+            "_Game" {
+                section! {
+                    "_New" => NewGameAction,
+                    "_Retry" => RetryAction,
+                    "New Game From _Seed..." => NewGameFromSeedAction,
+                    "_Undo" => UndoAction,
+                    "_Hint" => HintAction,
+                    "Auto-_Solve" => AutoSolveAction,
+                    "_Pause" => PauseAction,
+                    "Copy _Result" => CopyResultAction,
+                    "Copy Board to _Clipboard" => CopyBoardAction,
+                    "S_tatistics..." => StatisticsAction,
+                },
+                section! {
+                    "_Save Game..." => SaveGameAction,
+                    "_Load Game..." => LoadGameAction,
+                    "Load Board From _Text..." => LoadBoardFromTextAction,
+                    "Save Re_play..." => SaveReplayAction,
+                    "_Watch Replay..." => WatchReplayAction,
+                },
                 section! {
-                    "New" => NewGameAction,
+                    "_Beginner" => SetDifficultyBeginnerAction,
+                    "_Intermediate" => SetDifficultyIntermediateAction,
+                    "_Expert" => SetDifficultyExpertAction,
+                    "_Custom..." => SetDifficultyCustomAction,
+                    "_Level" {
+                        "Level 1" => SetDifficultyLevelAction(1_u8),
+                        "Level 2" => SetDifficultyLevelAction(2_u8),
+                        "Level 3" => SetDifficultyLevelAction(3_u8),
+                        "Level 4" => SetDifficultyLevelAction(4_u8),
+                        "Level 5" => SetDifficultyLevelAction(5_u8),
+                        "Level 6" => SetDifficultyLevelAction(6_u8),
+                        "Level 7" => SetDifficultyLevelAction(7_u8),
+                        "Level 8" => SetDifficultyLevelAction(8_u8),
+                        "Level 9" => SetDifficultyLevelAction(9_u8),
+                        "Level 10" => SetDifficultyLevelAction(10_u8),
+                    },
                 },
                 section! {
-                    "Beginner" => SetDifficultyBeginnerAction,
-                    "Intermediate" => SetDifficultyIntermediateAction,
-                    "Expert" => SetDifficultyExpertAction,
-                    "Custom..." => SetDifficultyCustomAction,
+                    "_Sound" => SoundAction,
                 },
                 section! {
-                    "Exit" => ExitAction,
+                    "E_xit" => ExitAction,
+                },
+            },
+            "_Options" {
+                "Auto-Chord on Flag" => AutoChordOnFlagAction,
+                "Open All Zeros" => OpenAllZerosAction,
+                "First Click Opens" => FirstClickOpeningAction,
+                "Start Timer at 1" => TimerHeadStartAction,
+                "Guard Unsafe Chords" => GuardUnsafeChordsAction,
+                "Click Numbers to Chord" => SmartClickAction,
+                "Marks (?)" => QuestionMarksAction,
+                "Number Symbols" => NumberGlyphsAction,
+                "Animate Openings" => RevealAnimationAction,
+                "Highlight New Cells" => RevealHighlightAction,
+                "Pause When Inactive" => AutoPauseAction,
+                "Show Start Hint" => StartHintAction,
+                "Verbose Announcements" => VerboseAnnouncementsAction,
+                "Worry When Over-Flagged" => WorriedFaceAction,
+                "Outline Patterns" => PatternHintsAction,
+                "Show Mine Odds" => MineOddsAction,
+                "Show Safe Cells Left" => SafeCellsBadgeAction,
+                "Show Game Result" => ResultDialogAction,
+                "Cell Si_ze" {
+                    section! {
+                        "Zoom _In" => ZoomInAction,
+                        "Zoom _Out" => ZoomOutAction,
+                    },
+                    section! {
+                        "Auto" => CellSizeAction(0),
+                        "Small" => CellSizeAction(16),
+                        "Medium" => CellSizeAction(20),
+                        "Large" => CellSizeAction(28),
+                    },
+                },
+                "_Theme" {
+                    "Classic 95" => ThemeAction(Theme::Classic.id()),
+                    "Dark" => ThemeAction(Theme::Dark.id()),
+                    "High Contrast" => ThemeAction(Theme::HighContrast.id()),
+                },
+                "Color_blind Numbers" {
+                    "Off" => NumberPaletteAction(NumberPalette::Standard.id()),
+                    "Deuteranopia" => NumberPaletteAction(NumberPalette::Deuteranopia.id()),
+                    "Protanopia" => NumberPaletteAction(NumberPalette::Protanopia.id()),
                 },
             },
-            "Help" {
-                "About Rusty Minesweeper..." => AboutAction
+            "_Help" {
+                "Watch _Demo" => DemoAction,
+                "_About Rusty Minesweeper..." => AboutAction
             },
         }
     }
@@ -195,153 +736,1607 @@ impl SimpleComponent for App {
         root: Self::Root,
         sender: relm4::ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let game_state = GameState::new(difficulty).expect("Failed to create game state");
+        let mut game_state = GameState::new(difficulty).expect("Failed to create game state");
+        game_state.set_best_times(BestTimes::load(&best_times_path()));
 
         let cells: FactoryVecDeque<ButtonCell> =
             FactoryVecDeque::builder().launch_default().detach();
 
-        // let sender_clone = sender.clone();
-        // gtk::glib::timeout_add_seconds_local(1, move || {
-        //     sender_clone.input(Msg::Tick);
-        //     ControlFlow::Continue
-        // });
-
-        // test fast tick to decrease the lag on difficulty change
-        let sender_clone = sender.clone();
-        gtk::glib::timeout_add_local(Duration::from_millis(4), move || {
-            sender_clone.input(Msg::Tick);
-            ControlFlow::Continue
-        });
-
         let model = Self::new(game_state, cells);
 
         let cells_grid = model.cells.widget();
 
         let widgets = view_output!();
-        Self::setup_actions(sender, &widgets.main_window);
+        Self::setup_actions(sender, &widgets.main_window, &model.settings);
 
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, _sender: relm4::ComponentSender<Self>) {
+    fn update(&mut self, message: Self::Input, sender: relm4::ComponentSender<Self>) {
+        // The board is hidden while paused, so it can't be played either.
+        // Replays play it by themselves.
+        if (self.game_state.is_paused() || self.replay.is_watching()) && is_board_input(&message) {
+            return;
+        }
+
         match message {
-            Msg::Restart => self.handle_restart(),
-            Msg::Tick => self.game_state.tick(),
-            Msg::ChangeDifficulty(difficulty) => self.handle_difficulty_change(difficulty),
+            Msg::Restart => self.handle_restart(None),
+            Msg::Retry => self.start_over(GameState::restart_same_board),
+            Msg::CloseEndStats => self.end_stats.open = false,
+            Msg::FaceClicked => {
+                if self.game_state.is_paused() {
+                    self.toggle_pause();
+                } else {
+                    self.handle_restart(None);
+                }
+            }
+            Msg::TogglePause => self.toggle_pause(),
+            Msg::RestartWithSeed(seed) => self.handle_restart(Some(seed)),
+            Msg::ShowNewGameFromSeed => self.show_new_game_from_seed_dialog(&sender),
+            Msg::Undo => self.undo(),
+            Msg::Hint => self.show_hint(&sender),
+            Msg::ClearHint(index) => {
+                if self.hint_cell == Some(index) {
+                    self.hint_cell = None;
+                    self.cells
+                        .send(index, ButtonMsg::RemoveCssClass(HINT_CLASS.to_string()));
+                }
+            }
+            Msg::Quit => self.quit(),
+            Msg::Tick(id) => {
+                if id == self.clock.id {
+                    self.clock.scheduled = false;
+                }
+            }
+            Msg::ChangeDifficulty(preset) => self.handle_difficulty_change(preset),
+            Msg::ShowCustomDifficulty => self.show_custom_difficulty_dialog(&sender),
             Msg::ShowAbout => Self::show_about_dialog(),
+            Msg::ShowStatistics => self.show_statistics_dialog(),
+            Msg::ToggleSound(enabled) => self.sound.set_enabled(enabled),
+            Msg::ToggleAutoChordOnFlag(enabled) => {
+                self.change_options(|options| options.auto_chord_on_flag = enabled);
+            }
+            Msg::ToggleOpenAllZeros(enabled) => {
+                self.change_options(|options| options.open_all_zeros = enabled);
+            }
+            Msg::ToggleFirstClickOpening(enabled) => {
+                self.change_options(|options| options.first_click_opening = enabled);
+            }
+            Msg::ToggleTimerHeadStart(enabled) => {
+                self.change_options(|options| options.timer_head_start = enabled);
+            }
+            Msg::ToggleGuardUnsafeChords(enabled) => {
+                self.change_options(|options| options.guard_unsafe_chords = enabled);
+            }
+            Msg::ToggleQuestionMarks(enabled) => self.change_options(|options| {
+                options.mark_cycle = if enabled {
+                    MarkCycle::Three
+                } else {
+                    MarkCycle::Two
+                };
+            }),
+            Msg::ToggleNumberGlyphs(enabled) => {
+                self.number_glyphs = enabled;
+                self.cells
+                    .broadcast(ButtonMsg::SetNumberGlyphs(self.shows_number_glyphs()));
+            }
+            Msg::ToggleRevealAnimation(enabled) => {
+                self.finish_reveal_animation();
+                self.reveal_animation.enabled = enabled;
+            }
+            Msg::RevealWave => self.reveal_next_wave(),
+            Msg::ToggleRevealHighlight(enabled) => {
+                self.clear_reveal_highlight();
+                self.reveal_highlight.enabled = enabled;
+            }
+            Msg::ClearRevealHighlight(batch) => {
+                if batch == self.reveal_highlight.batch {
+                    self.clear_reveal_highlight();
+                }
+            }
+            Msg::ToggleAutoPause(enabled) => {
+                self.auto_pause = enabled;
+                if !enabled && !self.paused_by_player {
+                    self.game_state.resume();
+                    self.reset_clock();
+                }
+            }
+            Msg::WindowActiveChanged(active) => {
+                if active {
+                    if !self.paused_by_player {
+                        self.game_state.resume();
+                        self.reset_clock();
+                    }
+                } else if self.auto_pause {
+                    self.game_state.pause();
+                }
+            }
+            Msg::ToggleStartHint(enabled) => self.start_hint = enabled,
+            Msg::ToggleSmartClick(enabled) => self.smart_click = enabled,
+            Msg::ToggleVerboseAnnouncements(enabled) => self.verbose_announcements = enabled,
+            Msg::ToggleWorriedFace(enabled) => self.worried_face = enabled,
+            Msg::TogglePatternHints(enabled) => {
+                self.pattern_hints = enabled;
+                self.refresh_pattern_hints();
+            }
+            Msg::ToggleMineOdds(enabled) => {
+                self.mine_odds = enabled;
+                self.refresh_mine_odds();
+            }
+            Msg::ToggleSafeCellsBadge(enabled) => self.safe_cells_badge = enabled,
+            Msg::ToggleResultDialog(enabled) => {
+                self.settings.result_dialog = enabled;
+                self.save_settings();
+            }
+            Msg::SetCellSize(size) => {
+                self.settings.cell_size = size;
+                self.apply_cell_size();
+                self.save_settings();
+            }
+            Msg::Zoom(step) => self.zoom(step),
+            Msg::SetTheme(theme) => {
+                self.settings.theme = theme;
+                self.apply_theme();
+            }
+            Msg::SetNumberPalette(palette) => {
+                self.settings.number_palette = palette;
+                self.apply_theme();
+                self.cells
+                    .broadcast(ButtonMsg::SetNumberGlyphs(self.shows_number_glyphs()));
+            }
+            Msg::CopySeed => self.copy_seed(),
+            Msg::CopyResult => self.copy_result(),
+            Msg::CopyBoard => self.copy_board(),
+            Msg::ShowSaveGame => Self::show_save_game_dialog(&sender),
+            Msg::ShowLoadGame => Self::show_load_game_dialog(&sender),
+            Msg::SaveGame(path) => match self.game_state.save_to_path(&path) {
+                Ok(()) => self.show_status_message("Game saved".to_string(), false),
+                Err(err) => self.report_error(&err),
+            },
+            Msg::LoadGame(path) => self.load_game(&path),
+            Msg::ShowLoadBoardFromText => self.show_load_board_from_text_dialog(&sender),
+            Msg::LoadBoardFromText(ascii) => self.load_board_from_text(&ascii),
+            Msg::ShowSaveReplay => Self::show_save_replay_dialog(&sender),
+            Msg::ShowWatchReplay => Self::show_watch_replay_dialog(&sender),
+            Msg::SaveReplay(path) => self.save_replay(&path),
+            Msg::WatchReplay(path) => self.watch_replay(&path),
+            Msg::ReplayStep(id) => self.play_replay_step(id),
+            Msg::HideLuckyToast => self.lucky_toast.visible = false,
+            Msg::ClearStatusMessage(id) => {
+                if id == self.status_message.id {
+                    self.status_message.text = None;
+                }
+            }
+            Msg::StartDemo => self.start_demo(&sender),
+            Msg::AutoSolve => self.start_auto_solve(&sender),
+            Msg::DemoMove => self.play_demo_move(),
             Msg::TrackMouse(x, y) => self.track_mouse(x, y),
             Msg::LeftButtonPressed => self.leftbutton_pressed(),
             Msg::LeftButtonReleased => self.leftbutton_released(),
             Msg::RightButtonPressed => self.rightbutton_pressed(),
             Msg::MiddleButtonPressed => self.middlebutton_pressed(),
             Msg::MiddleButtonReleased => self.middlebutton_released(),
+            Msg::KeyPressed(key) => self.key_pressed(key),
+            Msg::MoveFocus(dx, dy) => self.move_focus(dx, dy),
+            Msg::KeyReleased(key) => self.key_released(key),
+        }
+
+        self.schedule_reveal_animation(&sender);
+        self.schedule_reveal_highlight_clear(&sender);
+        self.schedule_lucky_toast(&sender);
+        self.save_new_best_time();
+        self.record_finished_game(&sender);
+        self.schedule_status_message_clear(&sender);
+        self.schedule_tick(&sender);
+        self.schedule_replay_step(&sender);
+        self.update_end_stats();
+        if self.game_state.cells_changed_since(self.drawn_generation) {
+            self.drawn_generation = self.game_state.generation();
+            self.refresh_pattern_hints();
+            self.refresh_mine_odds();
+        }
+        // Rated once per game, as soon as the first click generates the board
+        if self.complexity.is_none() {
+            self.complexity = self
+                .game_state
+                .complexity()
+                .map(|complexity| complexity.rating());
         }
     }
 }
 
 impl App {
-    fn new(game_state: GameState, mut cells: FactoryVecDeque<ButtonCell>) -> Self {
-        let board_size = game_state.difficulty().board_size;
-
-        let mut cells_guard = cells.guard();
+    fn new(game_state: GameState, cells: FactoryVecDeque<ButtonCell>) -> Self {
+        let mut settings = Settings::load(&settings_path());
+        settings.cell_size = settings
+            .cell_size
+            .map(|size| size.clamp(ZOOM_MIN_CELL_SIZE, ZOOM_MAX_CELL_SIZE));
+        let mut app = Self {
+            game_state,
+            cells,
+            mouse_tracker: MouseTracker::new(),
+            sound: SoundPlayer::new(),
+            flags_hidden: false,
+            number_glyphs: false,
+            reveal_animation: RevealAnimation::new(),
+            reveal_highlight: RevealHighlight::new(),
+            auto_pause: false,
+            start_hint: false,
+            smart_click: false,
+            verbose_announcements: false,
+            announcement: String::new(),
+            focused_cell: CellPosition::new(0, 0),
+            hint_cell: None,
+            paused_by_player: false,
+            demo_timer: None,
+            worried_face: false,
+            pattern_hints: false,
+            pattern_cells: Vec::new(),
+            mine_odds: false,
+            mine_odds_cells: HashMap::new(),
+            safe_cells_badge: false,
+            unsafe_chord_cell: None,
+            complexity: None,
+            drawn_generation: 0,
+            lucky_toast: LuckyToast::default(),
+            status_message: StatusMessage::default(),
+            clock: Clock::default(),
+            end_stats: EndStats::default(),
+            statistics: Statistics::load(&statistics_path()),
+            replay: ReplayPlayback::default(),
+            theme_provider: ThemeProvider::new(settings.theme, settings.number_palette),
+            settings,
+        };
+
+        let board_size = app.game_state.difficulty().board_size;
+        let cell_size = app.cell_size();
+        let mut cells_guard = app.cells.guard();
 
         for x in 0..board_size.0 {
             for y in 0..board_size.1 {
-                cells_guard.push_back(ButtonCell::new(CellPosition::new(x, y)));
+                cells_guard.push_back(ButtonCell::new(CellPosition::new(x, y), cell_size));
             }
         }
         cells_guard.drop();
+        app.focus_center();
+        app.cells
+            .broadcast(ButtonMsg::SetNumberGlyphs(app.shows_number_glyphs()));
 
-        Self {
-            game_state,
-            cells,
-            mouse_tracker: MouseTracker::new(),
+        app
+    }
+
+    /// Returns the cell size the player chose, or the one that fits the board
+    /// on screen.
+    fn cell_size(&self) -> i32 {
+        self.settings.cell_size.unwrap_or_else(|| {
+            let auto_size = self
+                .game_state
+                .difficulty()
+                .auto_cell_size(AUTO_FIT_BOARD_SIZE);
+            i32::try_from(auto_size).unwrap_or(i32::MAX)
+        })
+    }
+
+    /// Changes the player's options. A replay on the board keeps the options
+    /// it was played with, the player's take effect once it is left.
+    fn change_options(&mut self, change: impl FnOnce(&mut GameOptions)) {
+        if let Some(player_options) = self.replay.player_options.as_mut() {
+            change(player_options);
+            return;
         }
+
+        let mut options = *self.game_state.options();
+        change(&mut options);
+        self.game_state.set_options(options);
+    }
+
+    /// Restyles the window with the theme and number palette of the
+    /// settings, and saves them for the next launch.
+    fn apply_theme(&mut self) {
+        self.theme_provider
+            .apply(self.settings.theme, self.settings.number_palette);
+        self.save_settings();
     }
 
-    fn handle_restart(&mut self) {
-        self.game_state
-            .restart()
-            .expect("Failed to restart game. Bad difficulty?");
+    fn save_settings(&mut self) {
+        if let Err(err) = self.settings.save(&settings_path()) {
+            self.report_error(&err);
+        }
+    }
 
-        self.cells.broadcast(ButtonMsg::Display(EMPTY_STRING));
-        self.cells.broadcast(ButtonMsg::Reset);
+    /// Returns whether numbers show their symbols, which colorblind palettes
+    /// always do.
+    const fn shows_number_glyphs(&self) -> bool {
+        self.number_glyphs || self.settings.number_palette.is_colorblind()
     }
 
-    fn handle_reveal(&mut self, cell_pos: CellPosition) {
-        let reveal_result = self.game_state.reveal_cell(cell_pos);
+    /// Makes the cells `step` pixels bigger, or smaller if negative, within
+    /// the zoom range. The size is set through the Cell Size action, so the
+    /// menu shows it too.
+    fn zoom(&mut self, step: i32) {
+        let size = self.cell_size();
+        let zoomed = (size + step).clamp(ZOOM_MIN_CELL_SIZE, ZOOM_MAX_CELL_SIZE);
+        if zoomed == size {
+            return;
+        }
+
+        if let Err(err) = self
+            .cells
+            .widget()
+            .activate_action("win.cell-size", Some(&zoomed.to_variant()))
+        {
+            self.show_status_message(err.to_string(), true);
+        }
+    }
+
+    /// Resizes every cell to the current cell size.
+    fn apply_cell_size(&self) {
+        self.cells
+            .broadcast(ButtonMsg::SetCellSize(self.cell_size()));
+    }
+
+    /// Starts a new game, with its board generated from `seed` if given.
+    fn handle_restart(&mut self, seed: Option<u64>) {
+        self.start_over(|game_state| game_state.restart_with_seed(seed));
+    }
+
+    /// Restarts the game with `restart`, which keeps the difficulty, and
+    /// clears what the board showed.
+    fn start_over(&mut self, restart: impl FnOnce(&mut GameState) -> GameResult<()>) {
+        self.clear_board_ui();
+        restart(&mut self.game_state).expect("Failed to restart game. Bad difficulty?");
+
+        self.show_full_board();
+        self.focus_center();
+    }
+
+    /// Stops whatever plays on its own and drops everything the board showed
+    /// on top of the cells, before the game starts over or is replaced.
+    fn clear_board_ui(&mut self) {
+        self.stop_demo();
+        self.stop_replay();
+        self.reveal_animation.cancel();
+        self.reveal_highlight.cells.clear();
+        self.pattern_cells.clear();
+        self.mine_odds_cells.clear();
+        self.unsafe_chord_cell = None;
+        self.hint_cell = None;
+        self.paused_by_player = false;
+        self.complexity = None;
+        self.lucky_toast = LuckyToast::default();
+        self.announcement.clear();
+        self.reset_clock();
+    }
+
+    /// Reveals a safe cell for the player and makes it pulse for a moment.
+    /// Hints are counted, and a game with any can't set a best time.
+    fn show_hint(&mut self, sender: &relm4::ComponentSender<Self>) {
+        let Some(cell_pos) = self.game_state.hint() else {
+            return;
+        };
+        self.handle_reveal(cell_pos);
 
-        if let Ok(reveal_result) = reveal_result {
-            self.reveal_cells(&reveal_result);
+        if let Some(index) = self.hint_cell.take() {
+            self.cells
+                .send(index, ButtonMsg::RemoveCssClass(HINT_CLASS.to_string()));
         }
+        let index = cell_pos.to_index(self.game_state.difficulty().board_size);
+        self.cells
+            .send(index, ButtonMsg::AddCssClass(HINT_CLASS.to_string()));
+        self.hint_cell = Some(index);
+
+        let sender = sender.clone();
+        gtk::glib::timeout_add_local_once(HINT_PULSE_DURATION, move || {
+            sender.input(Msg::ClearHint(index));
+        });
     }
 
-    fn reveal_cells(&mut self, reveal_result: &RevealResult) {
+    /// Takes back the last action and redraws what it changed.
+    fn undo(&mut self) {
+        let Some(changed_cells) = self.game_state.undo() else {
+            return;
+        };
+        self.stop_demo();
+        self.clear_reveal_highlight();
+        if let Some(index) = self.unsafe_chord_cell.take() {
+            self.cells.send(
+                index,
+                ButtonMsg::RemoveCssClass(UNSAFE_CHORD_CLASS.to_string()),
+            );
+        }
+        self.lucky_toast = LuckyToast::default();
+        // Undoing the first reveal gives a board that isn't generated yet
+        if self.game_state.status().is_new() {
+            self.complexity = None;
+        }
+
+        // Cells still waiting to be shown may be anywhere on the board
+        if self.reveal_animation.is_running() {
+            self.reveal_animation.cancel();
+            self.show_full_board();
+            return;
+        }
+
         let board_size = self.game_state.difficulty().board_size;
+        for pos in changed_cells {
+            let Ok((label, css_classes)) = self.game_state.cell_display(pos) else {
+                continue;
+            };
+            let index = pos.to_index(board_size);
+            self.cells.send(index, ButtonMsg::Reset);
+            self.cells.send(index, ButtonMsg::Display(label));
+            for css_class in css_classes {
+                self.cells.send(index, ButtonMsg::AddCssClass(css_class));
+            }
+        }
+    }
 
-        for &revealed_pos in self.game_state.revealed_cells() {
-            let revealed_index = revealed_pos.to_index(board_size);
-            if let Ok(display) = self.game_state.display_cell(revealed_pos) {
-                self.cells.send(revealed_index, ButtonMsg::Display(display));
-                self.cells.send(
-                    revealed_index,
-                    ButtonMsg::AddCssClass(REVEALED_CELL_CLASS.to_string()),
-                );
+    /// Redraws every cell from the game state.
+    fn show_full_board(&self) {
+        for (index, label, css_classes) in self.game_state.full_display() {
+            self.cells.send(index, ButtonMsg::Reset);
+            self.cells.send(index, ButtonMsg::Display(label));
+            for css_class in css_classes {
+                self.cells.send(index, ButtonMsg::AddCssClass(css_class));
             }
         }
-        self.game_state.clear_revealed_cells();
+    }
 
-        match reveal_result {
-            RevealResult::Continue => {
-                if self.game_state.status().is_won() {
-                    for flagged_pos in self.game_state.flagged_cells() {
-                        let flag_index = flagged_pos.to_index(board_size);
-                        self.cells
-                            .send(flag_index, ButtonMsg::Display("🚩".to_string()));
-                    }
-                    self.game_state.clear_flagged_cells();
+    /// Pauses the game in progress, or resumes it if it is paused.
+    fn toggle_pause(&mut self) {
+        if self.game_state.is_paused() {
+            self.paused_by_player = false;
+            self.game_state.resume();
+            self.reset_clock();
+            return;
+        }
+
+        self.game_state.pause();
+        if !self.game_state.is_paused() {
+            return;
+        }
+        self.paused_by_player = true;
+        self.stop_demo();
+
+        // The release of a click held down when the pause came is ignored, so
+        // the pressed look is taken off now
+        if self.mouse_tracker.is_pressed() {
+            if let Some(cell_pos) = self.mouse_tracker.mouse_cell {
+                self.deactivate_cell(cell_pos, &MouseButton::Middle);
+            }
+        }
+        self.mouse_tracker.lbutton_state = MouseState::Released;
+        self.mouse_tracker.mbutton_state = MouseState::Released;
+    }
+
+    /// Starts a new game that the solver plays by itself, one move at a time.
+    fn start_demo(&mut self, sender: &relm4::ComponentSender<Self>) {
+        self.handle_restart(None);
+        self.start_solver_moves(sender);
+    }
+
+    /// Lets the solver finish the current game, one move at a time, until it
+    /// is won or the next move would be a guess.
+    fn start_auto_solve(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.game_state.status().is_over() {
+            return;
+        }
+
+        self.stop_demo();
+        self.start_solver_moves(sender);
+    }
+
+    fn start_solver_moves(&mut self, sender: &relm4::ComponentSender<Self>) {
+        let sender = sender.clone();
+        self.demo_timer = Some(gtk::glib::timeout_add_local(
+            DEMO_MOVE_INTERVAL,
+            move || {
+                sender.input(Msg::DemoMove);
+                ControlFlow::Continue
+            },
+        ));
+    }
+
+    /// Plays the solver's next move. The solver stops when the game is over
+    /// or the next move would be a guess, and tells why unless it won.
+    fn play_demo_move(&mut self) {
+        if self.demo_timer.is_none() {
+            return;
+        }
+
+        let Some(next_move) = Solver::next_move(&self.game_state) else {
+            self.stop_demo();
+            let outcome = AutoSolveOutcome::of(self.game_state.status());
+            if outcome != AutoSolveOutcome::Solved {
+                self.show_status_message(outcome.to_string(), false);
+            }
+            return;
+        };
+
+        match (next_move, self.game_state.play_solver_move(next_move)) {
+            (Move::Reveal(cell_pos), Ok(reveal_result)) => {
+                self.reveal_cells(&reveal_result, cell_pos);
+            }
+            (Move::Flag(cell_pos), Ok(_)) => {
+                self.sound.play_flag();
+                self.redraw_cell(cell_pos);
+                if let Some(auto_chord_result) = self.game_state.take_auto_chord_result() {
+                    self.reveal_cells(&auto_chord_result, cell_pos);
                 }
             }
-            RevealResult::GameOver(mine_pos) => self.cells.send(
-                mine_pos.to_index(board_size),
-                ButtonMsg::AddCssClass(LOST_CELL_CLASS.to_string()),
-            ),
-            RevealResult::CantReveal => (),
+            (_, Err(err)) => {
+                self.stop_demo();
+                self.report_error(&err);
+            }
+        }
+    }
+
+    fn stop_demo(&mut self) {
+        if let Some(timer) = self.demo_timer.take() {
+            timer.remove();
         }
     }
 
-    fn handle_flag(&mut self, cell_pos: CellPosition) {
-        if matches!(self.game_state.toggle_flag(cell_pos), Ok(true)) {
-            if let Ok(display) = self.game_state.display_cell(cell_pos) {
-                self.cells.send(
-                    cell_pos.to_index(self.game_state.difficulty().board_size),
-                    ButtonMsg::Display(display),
-                );
-            }
+    fn handle_reveal(&mut self, cell_pos: CellPosition) {
+        match self.game_state.reveal_cell(cell_pos) {
+            Ok(reveal_result) => self.reveal_cells(&reveal_result, cell_pos),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Reveals the cell, or chords it when it is a satisfied number.
+    fn handle_primary_action(&mut self, cell_pos: CellPosition) {
+        match self.game_state.primary_action(cell_pos) {
+            Ok(action_result) => self.reveal_cells(&action_result, cell_pos),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Shows an unexpected error in the status bar for a while, so it doesn't
+    /// go unnoticed.
+    fn report_error(&mut self, err: &GameError) {
+        self.show_status_message(err.to_string(), true);
+    }
+
+    /// Shows a message in the status bar for a while.
+    fn show_status_message(&mut self, text: String, error: bool) {
+        self.status_message.text = Some(text);
+        self.status_message.error = error;
+        self.status_message.id += 1;
+        self.status_message.clear_scheduled = false;
+    }
+
+    /// Saves the best times when a win sets a new one, and tells the player.
+    fn save_new_best_time(&mut self) {
+        if !self.game_state.take_new_best_time() {
+            return;
+        }
+
+        let seconds = self.game_state.elapsed_seconds();
+        let text = match self.game_state.score_category() {
+            ScoreCategory::Standard => format!("New best time: {seconds}s!"),
+            category => format!("New best time: {seconds}s! ({category})"),
+        };
+        match self.flush_persistence() {
+            Ok(()) => self.show_status_message(text, false),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    fn schedule_status_message_clear(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.status_message.clear_scheduled || self.status_message.text.is_none() {
+            return;
+        }
+
+        let sender = sender.clone();
+        let id = self.status_message.id;
+        gtk::glib::timeout_add_local_once(STATUS_MESSAGE_DURATION, move || {
+            sender.input(Msg::ClearStatusMessage(id));
+        });
+        self.status_message.clear_scheduled = true;
+    }
+
+    /// Works out the statistics of a game that just ended and opens them,
+    /// once per game. They are cleared as soon as the game is played again.
+    fn update_end_stats(&mut self) {
+        if !self.game_state.status().is_over() {
+            self.end_stats = EndStats::default();
+            return;
+        }
+        if self.end_stats.three_bv.is_some() {
+            return;
+        }
+
+        self.end_stats = EndStats {
+            open: true,
+            three_bv: self.game_state.board_3bv(),
+            three_bv_per_second: self.game_state.three_bv_per_second(),
+        };
+    }
+
+    /// Schedules the next redraw of the timer, for when it shows its next
+    /// second. Nothing is scheduled while the timer is stopped.
+    fn schedule_tick(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.clock.scheduled {
+            return;
+        }
+        let Some(delay) = self.game_state.tick(Instant::now()) else {
+            return;
+        };
+
+        let sender = sender.clone();
+        let id = self.clock.id;
+        gtk::glib::timeout_add_local_once(delay, move || {
+            sender.input(Msg::Tick(id));
+        });
+        self.clock.scheduled = true;
+    }
+
+    /// Drops the scheduled tick, which may be out of step with a timer that
+    /// was just restarted or resumed.
+    fn reset_clock(&mut self) {
+        self.clock.id = self.clock.id.wrapping_add(1);
+        self.clock.scheduled = false;
+    }
+
+    /// Shows the newly revealed cells. With the reveal animation on, openings
+    /// spread out from `origin` one ring of cells at a time.
+    fn reveal_cells(&mut self, reveal_result: &RevealResult, origin: CellPosition) {
+        let board_size = self.game_state.difficulty().board_size;
+        let animate = self.reveal_animation.enabled && self.game_state.revealed_cells().len() > 1;
+        let mut waves: BTreeMap<usize, Vec<(usize, String)>> = BTreeMap::new();
+
+        // Only the cells of the latest action stay highlighted
+        self.clear_reveal_highlight();
+        self.reveal_highlight.batch += 1;
+        if let Some(index) = self.unsafe_chord_cell.take() {
+            self.cells.send(
+                index,
+                ButtonMsg::RemoveCssClass(UNSAFE_CHORD_CLASS.to_string()),
+            );
+        }
+
+        for &revealed_pos in self.game_state.revealed_cells() {
+            let revealed_index = revealed_pos.to_index(board_size);
+            if let Ok(display) = self.game_state.display_cell(revealed_pos) {
+                if animate {
+                    let distance = revealed_pos
+                        .x
+                        .abs_diff(origin.x)
+                        .max(revealed_pos.y.abs_diff(origin.y));
+                    waves
+                        .entry(distance)
+                        .or_default()
+                        .push((revealed_index, display));
+                } else {
+                    self.show_revealed_cell(revealed_index, display);
+                }
+            }
+        }
+        self.game_state.clear_revealed_cells();
+        self.reveal_animation.waves.extend(waves.into_values());
+
+        match reveal_result {
+            RevealResult::Continue(_) => {
+                if let Some(content) = self.game_state.last_reveal_content() {
+                    self.sound.play_reveal(content);
+                }
+
+                if self.game_state.status().is_won() {
+                    self.sound.play_win();
+                    for &flagged_pos in self.game_state.flagged_cells() {
+                        self.redraw_cell(flagged_pos);
+                    }
+                    self.game_state.clear_flagged_cells();
+                }
+            }
+            RevealResult::GameOver(_) => {
+                self.sound.play_explosion();
+                self.show_loss();
+            }
+            RevealResult::CantReveal(CantReveal::Flagged) => self.sound.play_blocked(),
+            RevealResult::CantReveal(CantReveal::UnsafeChord) => {
+                // Stays marked until the next reveal or chord
+                let index = origin.to_index(board_size);
+                self.cells.send(
+                    index,
+                    ButtonMsg::AddCssClass(UNSAFE_CHORD_CLASS.to_string()),
+                );
+                self.unsafe_chord_cell = Some(index);
+                self.sound.play_blocked();
+            }
+            RevealResult::CantReveal(_) => (),
+        }
+    }
+
+    fn show_loss(&self) {
+        let Some(loss_view) = self.game_state.loss_overlay() else {
+            return;
+        };
+        let board_size = self.game_state.difficulty().board_size;
+
+        self.cells.send(
+            loss_view.detonated.to_index(board_size),
+            ButtonMsg::AddCssClass(LOST_CELL_CLASS.to_string()),
+        );
+
+        for wrong_flag in loss_view.wrong_flags {
+            let index = wrong_flag.to_index(board_size);
+            self.cells
+                .send(index, ButtonMsg::Display(WRONG_FLAG_LABEL.to_string()));
+            self.cells
+                .send(index, ButtonMsg::AddCssClass(WRONG_FLAG_CLASS.to_string()));
+        }
+    }
+
+    fn handle_flag(&mut self, cell_pos: CellPosition) {
+        match self.game_state.cycle_mark(cell_pos) {
+            Ok(true) => {
+                self.sound.play_flag();
+                self.redraw_cell(cell_pos);
+            }
+            Ok(false) => (),
+            Err(err) => self.report_error(&err),
+        }
+
+        if let Some(auto_chord_result) = self.game_state.take_auto_chord_result() {
+            self.reveal_cells(&auto_chord_result, cell_pos);
+        }
+    }
+
+    fn redraw_cell(&self, cell_pos: CellPosition) {
+        if let Ok(display) = self.game_state.display_cell(cell_pos) {
+            self.cells.send(
+                cell_pos.to_index(self.game_state.difficulty().board_size),
+                ButtonMsg::Display(display),
+            );
+        }
+    }
+
+    fn show_revealed_cell(&mut self, index: usize, display: String) {
+        self.cells.send(index, ButtonMsg::Display(display));
+        self.cells.send(
+            index,
+            ButtonMsg::AddCssClass(REVEALED_CELL_CLASS.to_string()),
+        );
+
+        if self.reveal_highlight.enabled {
+            self.cells.send(
+                index,
+                ButtonMsg::AddCssClass(JUST_REVEALED_CLASS.to_string()),
+            );
+            self.reveal_highlight.cells.push(index);
+        }
+    }
+
+    fn schedule_reveal_highlight_clear(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.reveal_highlight.clear_scheduled || self.reveal_highlight.cells.is_empty() {
+            return;
+        }
+
+        let sender = sender.clone();
+        let batch = self.reveal_highlight.batch;
+        gtk::glib::timeout_add_local_once(REVEAL_HIGHLIGHT_DURATION, move || {
+            sender.input(Msg::ClearRevealHighlight(batch));
+        });
+        self.reveal_highlight.clear_scheduled = true;
+    }
+
+    fn clear_reveal_highlight(&mut self) {
+        for index in self.reveal_highlight.cells.drain(..) {
+            self.cells.send(
+                index,
+                ButtonMsg::RemoveCssClass(JUST_REVEALED_CLASS.to_string()),
+            );
+        }
+        self.reveal_highlight.clear_scheduled = false;
+    }
+
+    /// Outlines the numbers of the patterns on the board, if enabled.
+    fn refresh_pattern_hints(&mut self) {
+        for index in self.pattern_cells.drain(..) {
+            self.cells.send(
+                index,
+                ButtonMsg::RemoveCssClass(PATTERN_HINT_CLASS.to_string()),
+            );
+        }
+
+        if !self.pattern_hints || self.game_state.status().is_over() {
+            return;
+        }
+
+        let board_size = self.game_state.difficulty().board_size;
+        for (_, cells) in self.game_state.pattern_hints() {
+            for pos in cells {
+                let index = pos.to_index(board_size);
+                self.cells.send(
+                    index,
+                    ButtonMsg::AddCssClass(PATTERN_HINT_CLASS.to_string()),
+                );
+                self.pattern_cells.push(index);
+            }
+        }
+    }
+
+    /// Writes the chance of a mine on each hidden cell, if enabled. Only the
+    /// cells whose odds changed are redrawn.
+    fn refresh_mine_odds(&mut self) {
+        let board_size = self.game_state.difficulty().board_size;
+        let odds: HashMap<usize, String> = if self.mine_odds {
+            self.game_state
+                .mine_probabilities()
+                .into_iter()
+                .map(|(pos, probability)| {
+                    (
+                        pos.to_index(board_size),
+                        format!("{:.0}%", probability * 100.0),
+                    )
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        for index in self.mine_odds_cells.keys() {
+            if !odds.contains_key(index) {
+                self.cells.send(*index, ButtonMsg::SetOverlay(None));
+            }
+        }
+        for (index, text) in &odds {
+            if self.mine_odds_cells.get(index) != Some(text) {
+                self.cells
+                    .send(*index, ButtonMsg::SetOverlay(Some(text.clone())));
+            }
+        }
+        self.mine_odds_cells = odds;
+    }
+
+    /// Shows the lucky start toast for a while, once the first click of the
+    /// game opened a large part of the board.
+    fn schedule_lucky_toast(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.lucky_toast.checked || self.game_state.first_opening_size().is_none() {
+            return;
+        }
+
+        self.lucky_toast.checked = true;
+        if !self.game_state.is_lucky_start() {
+            return;
+        }
+
+        self.lucky_toast.visible = true;
+        let sender = sender.clone();
+        gtk::glib::timeout_add_local_once(LUCKY_TOAST_DURATION, move || {
+            sender.input(Msg::HideLuckyToast);
+        });
+    }
+
+    fn schedule_reveal_animation(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.reveal_animation.timer.is_some() || self.reveal_animation.waves.is_empty() {
+            return;
+        }
+
+        let sender = sender.clone();
+        self.reveal_animation.timer = Some(gtk::glib::timeout_add_local(
+            REVEAL_WAVE_INTERVAL,
+            move || {
+                sender.input(Msg::RevealWave);
+                ControlFlow::Continue
+            },
+        ));
+    }
+
+    fn reveal_next_wave(&mut self) {
+        if let Some(wave) = self.reveal_animation.waves.pop_front() {
+            for (index, display) in wave {
+                self.show_revealed_cell(index, display);
+            }
+        }
+
+        if self.reveal_animation.waves.is_empty() {
+            self.reveal_animation.cancel();
+        }
+    }
+
+    /// Shows everything the running reveal animation still has to show.
+    /// Returns `false` if no animation was running.
+    fn finish_reveal_animation(&mut self) -> bool {
+        if !self.reveal_animation.is_running() {
+            return false;
+        }
+
+        while !self.reveal_animation.waves.is_empty() {
+            self.reveal_next_wave();
+        }
+        self.reveal_animation.cancel();
+        true
+    }
+
+    fn handle_difficulty_change(&mut self, preset: Preset) {
+        let old_board_size = self.game_state.difficulty().board_size;
+        if let Err(err) = self.game_state.set_preset(preset) {
+            Self::show_error_dialog("Couldn't change the difficulty", &err);
+            return;
+        }
+        self.reset_board_view(old_board_size);
+
+        self.settings.difficulty = Some(*self.game_state.difficulty());
+        self.save_settings();
+    }
+
+    /// Tells the player why an action they asked for failed, in a dialog
+    /// over the window. The game is left as it was.
+    fn show_error_dialog(message: &str, err: &GameError) {
+        let dialog = gtk::AlertDialog::builder()
+            .modal(true)
+            .message(message)
+            .detail(err.to_string())
+            .build();
+        dialog.show(relm4::main_application().active_window().as_ref());
+    }
+
+    /// Replaces the game with the one saved at `path` and redraws the board
+    /// as it was saved, revealed numbers and flags included.
+    fn load_game(&mut self, path: &Path) {
+        let old_board_size = self.game_state.difficulty().board_size;
+        if let Err(err) = self.game_state.load_from_path(path) {
+            self.report_error(&err);
+            return;
+        }
+        self.reset_board_view(old_board_size);
+        self.show_status_message("Game loaded".to_string(), false);
+    }
+
+    /// Starts a game on the board drawn in `ascii`, see [`Board::from_ascii`].
+    fn load_board_from_text(&mut self, ascii: &str) {
+        let old_board_size = self.game_state.difficulty().board_size;
+        if let Err(err) =
+            Board::from_ascii(ascii).and_then(|board| self.game_state.load_board(board))
+        {
+            self.report_error(&err);
+            return;
+        }
+        self.reset_board_view(old_board_size);
+        self.show_status_message("Board loaded".to_string(), false);
+    }
+
+    fn save_replay(&mut self, path: &Path) {
+        let Some(replay) = self.game_state.replay() else {
+            self.show_status_message("Nothing to replay yet".to_string(), false);
+            return;
+        };
+
+        match replay.save(path) {
+            Ok(()) => self.show_status_message("Replay saved".to_string(), false),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Starts the game of the replay saved at `path` and plays its events
+    /// back on the game clock, with the options it was played with. The
+    /// board can't be played until a new game is started.
+    fn watch_replay(&mut self, path: &Path) {
+        let replay = match Replay::load(path) {
+            Ok(replay) => replay,
+            Err(err) => {
+                self.report_error(&err);
+                return;
+            }
+        };
+
+        // Gives back the options of a replay already on the board first
+        self.stop_replay();
+        let player_options = *self.game_state.options();
+        let old_board_size = self.game_state.difficulty().board_size;
+        if let Err(err) = self.game_state.start_replay(&replay) {
+            self.report_error(&err);
+            return;
+        }
+        self.reset_board_view(old_board_size);
+
+        self.replay.events = replay.events.into();
+        self.replay.player_options = Some(player_options);
+    }
+
+    /// Plays the next event of the replay through the same handlers as the
+    /// player's clicks and keys.
+    fn play_replay_step(&mut self, id: u64) {
+        if id != self.replay.id {
+            return;
+        }
+        self.replay.scheduled = false;
+        // Scheduled again on resume
+        if self.game_state.is_paused() {
+            return;
+        }
+        let Some(event) = self.replay.events.pop_front() else {
+            return;
+        };
+
+        match (event.action, event.pos) {
+            (ReplayAction::Reveal, Some(cell_pos)) => self.handle_reveal(cell_pos),
+            (ReplayAction::Chord, Some(cell_pos)) => self.handle_chord(cell_pos),
+            (ReplayAction::SmartChord, Some(cell_pos)) => self.handle_smart_chord(cell_pos),
+            (ReplayAction::Mark(mark), Some(cell_pos)) => self.handle_set_mark(cell_pos, mark),
+            (ReplayAction::Sweep, _) => self.handle_sweep_key(),
+            (ReplayAction::RevealAllZeros, _) => match self.game_state.reveal_all_zeros() {
+                Ok(0) => (),
+                Ok(count) => {
+                    self.reveal_cells(&RevealResult::Continue(count), CellPosition::new(0, 0));
+                }
+                Err(err) => self.report_error(&err),
+            },
+            (ReplayAction::Undo, _) => self.undo(),
+            // Only actions on the whole board come without a cell
+            (_, None) => (),
+        }
+
+        if self.replay.events.is_empty() {
+            self.show_status_message("Replay finished".to_string(), false);
+        }
+    }
+
+    /// Schedules the next event of the replay for when the game clock
+    /// reaches it. Nothing is scheduled while the game is paused.
+    fn schedule_replay_step(&mut self, sender: &relm4::ComponentSender<Self>) {
+        if self.replay.scheduled || self.game_state.is_paused() {
+            return;
+        }
+        let Some(event) = self.replay.events.front() else {
+            return;
+        };
+
+        let delay = event.at.saturating_sub(self.game_state.elapsed());
+        let sender = sender.clone();
+        let id = self.replay.id;
+        gtk::glib::timeout_add_local_once(delay, move || {
+            sender.input(Msg::ReplayStep(id));
+        });
+        self.replay.scheduled = true;
+    }
+
+    /// Stops the replay on the board, if any, and gives the player their
+    /// options back.
+    fn stop_replay(&mut self) {
+        if let Some(player_options) = self.replay.player_options.take() {
+            self.game_state.set_options(player_options);
+        }
+        self.replay.events.clear();
+        self.replay.id = self.replay.id.wrapping_add(1);
+        self.replay.scheduled = false;
+    }
+
+    /// Brings the UI back to a fresh state after the game was replaced, and
+    /// fits the grid to the new board if it was `old_board_size`.
+    fn reset_board_view(&mut self, old_board_size: (usize, usize)) {
+        self.clear_board_ui();
+        let difficulty = *self.game_state.difficulty();
+
+        // Reset mouse tracker
+        self.mouse_tracker = MouseTracker::new();
+
+        // The window is resized to fit the new grid but stays wherever the
+        // compositor puts it. Neither keeping its position nor re-centering it
+        // is possible: GTK4 dropped the APIs to read or move a toplevel
+        // window (gtk_window_move, gtk_window_set_position).
+        let resized = difficulty.board_size != old_board_size;
+        if resized {
+            self.resize_cells(difficulty.board_size);
+        }
+        self.show_full_board();
+        self.focus_center();
+
+        // New cells start with the default rendering
+        self.cells
+            .broadcast(ButtonMsg::SetNumberGlyphs(self.shows_number_glyphs()));
+        self.apply_cell_size();
+        if resized {
+            self.fit_window(difficulty.board_size);
+        }
+    }
+
+    /// Sizes the window for a grid of `board_size` cells. The kept cells only
+    /// take the new cell size once they handle its message, so the grid is
+    /// sized from the cell size rather than measured.
+    fn fit_window(&self, board_size: (usize, usize)) {
+        let grid = self.cells.widget();
+        let Some(window) = grid
+            .root()
+            .and_then(|root| root.downcast::<gtk::Window>().ok())
+        else {
+            return;
+        };
+        let Some(content) = window.child() else {
+            return;
+        };
+
+        let cell_size = self.cell_size();
+        let to_pixels = |cells: usize| {
+            i32::try_from(cells).map_or(i32::MAX, |cells| cells.saturating_mul(cell_size))
+        };
+        // Above and below the grid are the menu bar, the counters and face,
+        // and the status bar, all stacked in the content
+        let (_, content_height, _, _) = content.measure(gtk::Orientation::Vertical, -1);
+        let (_, grid_height, _, _) = grid.measure(gtk::Orientation::Vertical, -1);
+        // Beside it only margins. Rows wider than a small board keep the
+        // window from getting narrower than them.
+        let margins: i32 =
+            std::iter::successors(Some(grid.clone().upcast::<gtk::Widget>()), |widget| {
+                widget.parent()
+            })
+            .map(|widget| widget.margin_start() + widget.margin_end())
+            .sum();
+
+        window.set_default_size(
+            to_pixels(board_size.0).saturating_add(margins),
+            to_pixels(board_size.1).saturating_add(content_height - grid_height),
+        );
+        window.queue_resize();
+    }
+
+    /// Resizes the grid to `board_size`, see [`resize_grid`].
+    fn resize_cells(&mut self, board_size: (usize, usize)) {
+        let cell_size = self.cell_size();
+        resize_grid(&mut self.cells, board_size, cell_size);
+    }
+
+    /// Puts the seed of the current board on the clipboard, if it has one.
+    fn copy_seed(&self) {
+        let (Some(seed), Some(display)) = (self.game_state.seed(), gdk::Display::default()) else {
+            return;
+        };
+
+        display.clipboard().set_text(&seed.to_string());
+    }
+
+    /// Puts the result of the finished game on the clipboard as JSON. Does
+    /// nothing while the game is still being played.
+    fn copy_result(&mut self) {
+        let Some(display) = gdk::Display::default() else {
+            return;
+        };
+
+        match self.game_state.export_summary_json() {
+            Ok(Some(json)) => display.clipboard().set_text(&json),
+            Ok(None) => (),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Puts the board on the clipboard as text, for bug reports and sharing
+    /// positions. Does nothing before the first click.
+    fn copy_board(&self) {
+        let (Some(ascii), Some(display)) =
+            (self.game_state.export_ascii(), gdk::Display::default())
+        else {
+            return;
+        };
+
+        display.clipboard().set_text(&ascii);
+    }
+
+    /// Asks where to save the current game.
+    fn show_save_game_dialog(sender: &relm4::ComponentSender<Self>) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Save Game")
+            .modal(true)
+            .initial_name(SAVED_GAME_NAME)
+            .build();
+        let sender = sender.clone();
+        dialog.save(
+            relm4::main_application().active_window().as_ref(),
+            None::<&gtk::gio::Cancellable>,
+            move |file| {
+                if let Some(path) = file.ok().and_then(|file| file.path()) {
+                    sender.input(Msg::SaveGame(path));
+                }
+            },
+        );
+    }
+
+    /// Asks which saved game to load.
+    fn show_load_game_dialog(sender: &relm4::ComponentSender<Self>) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Load Game")
+            .modal(true)
+            .build();
+        let sender = sender.clone();
+        dialog.open(
+            relm4::main_application().active_window().as_ref(),
+            None::<&gtk::gio::Cancellable>,
+            move |file| {
+                if let Some(path) = file.ok().and_then(|file| file.path()) {
+                    sender.input(Msg::LoadGame(path));
+                }
+            },
+        );
+    }
+
+    /// Asks where to save the replay of the current game.
+    fn show_save_replay_dialog(sender: &relm4::ComponentSender<Self>) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Save Replay")
+            .modal(true)
+            .initial_name(SAVED_REPLAY_NAME)
+            .build();
+        let sender = sender.clone();
+        dialog.save(
+            relm4::main_application().active_window().as_ref(),
+            None::<&gtk::gio::Cancellable>,
+            move |file| {
+                if let Some(path) = file.ok().and_then(|file| file.path()) {
+                    sender.input(Msg::SaveReplay(path));
+                }
+            },
+        );
+    }
+
+    /// Asks which replay to watch.
+    fn show_watch_replay_dialog(sender: &relm4::ComponentSender<Self>) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Watch Replay")
+            .modal(true)
+            .build();
+        let sender = sender.clone();
+        dialog.open(
+            relm4::main_application().active_window().as_ref(),
+            None::<&gtk::gio::Cancellable>,
+            move |file| {
+                if let Some(path) = file.ok().and_then(|file| file.path()) {
+                    sender.input(Msg::WatchReplay(path));
+                }
+            },
+        );
+    }
+
+    /// Quits once everything is saved. The window close button, the Exit
+    /// action and Ctrl+Q all end up here.
+    fn quit(&self) {
+        let Err(err) = self.flush_persistence() else {
+            relm4::main_application().quit();
+            return;
+        };
+
+        // Saving failing shouldn't keep the player from quitting
+        let dialog = gtk::AlertDialog::builder()
+            .modal(true)
+            .message("Couldn't save high scores and stats")
+            .detail(err.to_string())
+            .build();
+        dialog.choose(
+            relm4::main_application().active_window().as_ref(),
+            None::<&gtk::gio::Cancellable>,
+            |_| relm4::main_application().quit(),
+        );
+    }
+
+    /// Writes the best times and statistics to disk.
+    fn flush_persistence(&self) -> GameResult<()> {
+        self.game_state.best_times().save(&best_times_path())?;
+        self.statistics.save(&statistics_path())
+    }
+
+    /// Counts the game in the statistics once it ends, and saves them. Then
+    /// sums the game up, unless the player turned the result dialog off.
+    fn record_finished_game(&mut self, sender: &relm4::ComponentSender<Self>) {
+        let Some(status) = self.game_state.take_finished_status() else {
+            return;
+        };
+        // The solver's games and replays aren't the player's
+        if self.demo_timer.is_some() || self.replay.is_watching() {
+            return;
+        }
+
+        self.statistics.record(
+            self.game_state.difficulty(),
+            status.is_won(),
+            self.game_state.elapsed().as_secs(),
+            self.game_state.first_opening_size(),
+        );
+        if let Err(err) = self.flush_persistence() {
+            self.report_error(&err);
+        }
+
+        if self.settings.result_dialog {
+            self.show_result_dialog(status, sender);
+        }
+    }
+
+    /// Tells how the game ended and how long it took, and offers to play
+    /// again. The dialog isn't modal, so the final board can still be looked
+    /// at, and closing it leaves the game as it is.
+    fn show_result_dialog(&self, status: GameStatus, sender: &relm4::ComponentSender<Self>) {
+        let message = if status.is_won() {
+            "You won!"
+        } else {
+            "You hit a mine"
+        };
+        let dialog = gtk::AlertDialog::builder()
+            .modal(false)
+            .message(message)
+            .detail(format!(
+                "Time: {}",
+                format_duration(self.game_state.elapsed().as_secs())
+            ))
+            .buttons(["New Game", "Retry", "Close"])
+            .default_button(0)
+            .cancel_button(2)
+            .build();
+
+        let sender = sender.clone();
+        dialog.choose(
+            relm4::main_application().active_window().as_ref(),
+            None::<&gtk::gio::Cancellable>,
+            move |response| match response {
+                Ok(0) => sender.input(Msg::Restart),
+                Ok(1) => sender.input(Msg::Retry),
+                _ => (),
+            },
+        );
+    }
+
+    /// Shows the games played, won and the win streaks of each difficulty.
+    fn show_statistics_dialog(&self) {
+        let grid = gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        let attach_row = |row: i32, cells: [String; 8]| {
+            for (column, text) in (0..).zip(cells) {
+                let label = gtk::Label::builder()
+                    .label(text)
+                    .halign(if column == 0 {
+                        gtk::Align::Start
+                    } else {
+                        gtk::Align::End
+                    })
+                    .build();
+                grid.attach(&label, column, row, 1, 1);
+            }
+        };
+
+        attach_row(
+            0,
+            [
+                "Difficulty",
+                "Played",
+                "Won",
+                "Win %",
+                "Streak",
+                "Longest",
+                "Time",
+                "Opening",
+            ]
+            .map(str::to_string),
+        );
+        for (row, (name, statistics)) in (1..).zip(self.statistics.by_difficulty()) {
+            attach_row(
+                row,
+                [
+                    name.to_string(),
+                    statistics.games_played.to_string(),
+                    statistics.games_won.to_string(),
+                    format!("{:.0}%", statistics.win_percentage()),
+                    statistics.current_streak.to_string(),
+                    statistics.longest_streak.to_string(),
+                    format_duration(statistics.total_seconds),
+                    statistics
+                        .average_opening_size()
+                        .map_or_else(|| "-".to_string(), |size| format!("{size:.1}")),
+                ],
+            );
+        }
+
+        let close_button = gtk::Button::with_label("Close");
+        close_button.set_halign(gtk::Align::End);
+        grid.attach(&close_button, 0, 5, 8, 1);
+
+        let window = gtk::Window::builder()
+            .title("Statistics")
+            .modal(true)
+            .resizable(false)
+            .child(&grid)
+            .default_widget(&close_button)
+            .build();
+        window.set_transient_for(relm4::main_application().active_window().as_ref());
+
+        let window_clone = window.clone();
+        close_button.connect_clicked(move |_| window_clone.close());
+
+        window.present();
+    }
+
+    /// Asks for the board size and mines count of a custom game, starting
+    /// from the current difficulty. Values no board can be built with are
+    /// refused in the dialog, so the game only changes to a playable board.
+    fn show_custom_difficulty_dialog(&self, sender: &relm4::ComponentSender<Self>) {
+        let difficulty = self.game_state.difficulty();
+        let spin_button = |value: usize, max: u32| {
+            let spin_button = gtk::SpinButton::with_range(1.0, f64::from(max), 1.0);
+            spin_button.set_value(u32::try_from(value).map_or(f64::from(max), f64::from));
+            spin_button.set_activates_default(true);
+            spin_button
+        };
+
+        let width_spin = spin_button(difficulty.board_size.0, CUSTOM_MAX_SIDE);
+        let height_spin = spin_button(difficulty.board_size.1, CUSTOM_MAX_SIDE);
+        let mines_spin = spin_button(difficulty.mines_count, CUSTOM_MAX_SIDE * CUSTOM_MAX_SIDE);
+        let no_guess_check = gtk::CheckButton::builder()
+            .label("No guessing")
+            .tooltip_text(
+                "Only play boards that can be cleared without guessing. \
+                 Starting a dense board may take a while.",
+            )
+            .active(difficulty.no_guess)
+            .build();
+
+        let form = Self::form_grid();
+        for (row, (label, spin_button)) in (0..).zip([
+            ("Width", &width_spin),
+            ("Height", &height_spin),
+            ("Mines", &mines_spin),
+        ]) {
+            Self::attach_field(&form, row, label, spin_button);
+        }
+        form.attach(&no_guess_check, 0, 3, 2, 1);
+
+        let sender = sender.clone();
+        Self::show_form_dialog("Custom Difficulty", false, &form, move || {
+            let value_of = |spin_button: &gtk::SpinButton| {
+                usize::try_from(spin_button.value_as_int()).unwrap_or(0)
+            };
+            let (width, height) = (value_of(&width_spin), value_of(&height_spin));
+            let mines_count = value_of(&mines_spin);
+            if mines_count >= width * height {
+                return Err("There must be fewer mines than cells.".to_string());
+            }
+
+            let preset = Preset::Custom(width, height, mines_count, no_guess_check.is_active());
+            preset.difficulty().map_err(|err| err.to_string())?;
+            sender.input(Msg::ChangeDifficulty(preset));
+            Ok(())
+        });
+    }
+
+    /// Asks for the seed of a new game, starting from the seed of the current
+    /// one so a board can be replayed.
+    fn show_new_game_from_seed_dialog(&self, sender: &relm4::ComponentSender<Self>) {
+        let seed_entry = gtk::Entry::builder()
+            .input_purpose(gtk::InputPurpose::Digits)
+            .activates_default(true)
+            .build();
+        if let Some(seed) = self.game_state.current_seed() {
+            seed_entry.set_text(&seed.to_string());
+        }
+
+        let form = Self::form_grid();
+        Self::attach_field(&form, 0, "Seed", &seed_entry);
+
+        let sender = sender.clone();
+        Self::show_form_dialog("New Game From Seed", false, &form, move || {
+            let seed = seed_entry
+                .text()
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| "The seed must be a whole number.".to_string())?;
+            sender.input(Msg::RestartWithSeed(seed));
+            Ok(())
+        });
+    }
+
+    /// Asks for a board drawn as text, starting from the current one so it
+    /// can be edited. The text is checked before the dialog closes.
+    fn show_load_board_from_text_dialog(&self, sender: &relm4::ComponentSender<Self>) {
+        let text_view = gtk::TextView::builder().monospace(true).build();
+        if let Some(ascii) = self.game_state.export_ascii() {
+            text_view.buffer().set_text(&ascii);
         }
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .child(&text_view)
+            .min_content_width(320)
+            .min_content_height(240)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        let help_label = gtk::Label::builder()
+            .label("One line per row: * mine, F flagged mine, X flagged safe cell, # hidden, . or 1-8 revealed")
+            .halign(gtk::Align::Start)
+            .wrap(true)
+            .build();
+
+        let form = Self::form_grid();
+        form.attach(&help_label, 0, 0, 1, 1);
+        form.attach(&scrolled_window, 0, 1, 1, 1);
+
+        let sender = sender.clone();
+        Self::show_form_dialog("Load Board From Text", true, &form, move || {
+            let buffer = text_view.buffer();
+            let ascii = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            Board::from_ascii(&ascii).map_err(|err| err.to_string())?;
+            sender.input(Msg::LoadBoardFromText(ascii.to_string()));
+            Ok(())
+        });
     }
 
-    fn handle_difficulty_change(&mut self, difficulty: GameDifficulty) {
-        self.game_state
-            .change_difficulty(difficulty)
-            .expect("Failed to change difficulty. Bad difficulty?");
+    /// Returns an empty grid to lay the fields of a form dialog out on.
+    fn form_grid() -> gtk::Grid {
+        gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(12)
+            .build()
+    }
 
-        // Reset mouse tracker
-        self.mouse_tracker = MouseTracker::new();
+    /// Puts `field` in the grid row `row`, after a label naming it.
+    fn attach_field(grid: &gtk::Grid, row: i32, label: &str, field: &impl IsA<gtk::Widget>) {
+        let label = gtk::Label::builder()
+            .label(label)
+            .halign(gtk::Align::Start)
+            .build();
+        grid.attach(&label, 0, row, 1, 1);
+        grid.attach(field, 1, row, 1, 1);
+    }
 
-        let new_size = difficulty.board_size.0 * difficulty.board_size.1;
-        if self.cells.len() == new_size {
-            self.cells.broadcast(ButtonMsg::Reset);
-        } else {
-            let mut cells_guard = self.cells.guard();
-            cells_guard.clear();
+    /// Opens a modal dialog showing `form` above an error label and Cancel
+    /// and OK buttons. OK runs `submit`, which closes the dialog on `Ok`, and
+    /// on `Err` shows the message under the form and keeps the dialog open.
+    fn show_form_dialog(
+        title: &str,
+        resizable: bool,
+        form: &impl IsA<gtk::Widget>,
+        submit: impl Fn() -> Result<(), String> + 'static,
+    ) {
+        let error_label = gtk::Label::builder()
+            .css_classes(["status-error"])
+            .wrap(true)
+            .visible(false)
+            .build();
 
-            for x in 0..difficulty.board_size.0 {
-                for y in 0..difficulty.board_size.1 {
-                    cells_guard.push_back(ButtonCell::new(CellPosition::new(x, y)));
-                }
+        let cancel_button = gtk::Button::with_label("Cancel");
+        let ok_button = gtk::Button::with_label("OK");
+        let buttons = gtk::Box::builder()
+            .spacing(6)
+            .halign(gtk::Align::End)
+            .build();
+        buttons.append(&cancel_button);
+        buttons.append(&ok_button);
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        content.append(form);
+        content.append(&error_label);
+        content.append(&buttons);
+
+        let window = gtk::Window::builder()
+            .title(title)
+            .modal(true)
+            .resizable(resizable)
+            .child(&content)
+            .default_widget(&ok_button)
+            .build();
+        window.set_transient_for(relm4::main_application().active_window().as_ref());
+
+        let window_clone = window.clone();
+        cancel_button.connect_clicked(move |_| window_clone.close());
+
+        let window_clone = window.clone();
+        ok_button.connect_clicked(move |_| match submit() {
+            Ok(()) => window_clone.close(),
+            Err(message) => {
+                error_label.set_label(&message);
+                error_label.set_visible(true);
             }
-        }
+        });
+
+        window.present();
     }
 
     fn show_about_dialog() {
@@ -359,7 +2354,11 @@ impl App {
         dialog.present();
     }
 
-    fn setup_actions(sender: relm4::ComponentSender<Self>, window: &gtk::Window) {
+    fn setup_actions(
+        sender: relm4::ComponentSender<Self>,
+        window: &gtk::Window,
+        settings: &Settings,
+    ) {
         let mut group = RelmActionGroup::<WindowActionGroup>::new();
 
         let sender = Rc::new(sender);
@@ -374,13 +2373,23 @@ impl App {
             };
         }
 
-        add_difficulty_action!(SetDifficultyBeginnerAction, GameDifficulty::BEGINNER);
-        add_difficulty_action!(
-            SetDifficultyIntermediateAction,
-            GameDifficulty::INTERMEDIATE
+        add_difficulty_action!(SetDifficultyBeginnerAction, Preset::Beginner);
+        add_difficulty_action!(SetDifficultyIntermediateAction, Preset::Intermediate);
+        add_difficulty_action!(SetDifficultyExpertAction, Preset::Expert);
+
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<SetDifficultyCustomAction>::new_stateless(
+            move |_| {
+                sender_clone.input(Msg::ShowCustomDifficulty);
+            },
+        ));
+
+        let sender_clone = sender.clone();
+        group.add_action(
+            RelmAction::<SetDifficultyLevelAction>::new_with_target_value(move |_, level| {
+                sender_clone.input(Msg::ChangeDifficulty(Preset::Level(level)));
+            }),
         );
-        add_difficulty_action!(SetDifficultyExpertAction, GameDifficulty::EXPERT);
-        add_difficulty_action!(SetDifficultyCustomAction, GameDifficulty::CUSTOM);
 
         // New game action
         let sender_clone = sender.clone();
@@ -388,10 +2397,205 @@ impl App {
             sender_clone.input(Msg::Restart);
         }));
 
+        // Retry action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<RetryAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::Retry);
+        }));
+
+        // New game from seed action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<NewGameFromSeedAction>::new_stateless(
+            move |_| {
+                sender_clone.input(Msg::ShowNewGameFromSeed);
+            },
+        ));
+
+        // Hint action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<HintAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::Hint);
+        }));
+
+        // Undo action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<UndoAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::Undo);
+        }));
+        relm4::main_application().set_accels_for_action("win.undo", &["<Control>z"]);
+
+        // Pause action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<PauseAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::TogglePause);
+        }));
+        relm4::main_application().set_accels_for_action("win.pause", &["p"]);
+
+        // Copy result action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<CopyResultAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::CopyResult);
+        }));
+
+        // Copy board action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<CopyBoardAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::CopyBoard);
+        }));
+
+        // Save game action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<SaveGameAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::ShowSaveGame);
+        }));
+
+        // Load game action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<LoadGameAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::ShowLoadGame);
+        }));
+
+        // Load board from text action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<LoadBoardFromTextAction>::new_stateless(
+            move |_| {
+                sender_clone.input(Msg::ShowLoadBoardFromText);
+            },
+        ));
+
+        // Save replay action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<SaveReplayAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::ShowSaveReplay);
+        }));
+
+        // Watch replay action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<WatchReplayAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::ShowWatchReplay);
+        }));
+
+        macro_rules! add_toggle_action {
+            ($action:ty, $msg:path) => {
+                add_toggle_action!($action, $msg, false);
+            };
+            ($action:ty, $msg:path, $initial:expr) => {
+                let sender = sender.clone();
+                group.add_action(RelmAction::<$action>::new_stateful(
+                    &$initial,
+                    move |_, enabled| {
+                        *enabled = !*enabled;
+                        sender.input($msg(*enabled));
+                    },
+                ));
+            };
+        }
+
+        add_toggle_action!(SoundAction, Msg::ToggleSound);
+        add_toggle_action!(AutoChordOnFlagAction, Msg::ToggleAutoChordOnFlag);
+        add_toggle_action!(NumberGlyphsAction, Msg::ToggleNumberGlyphs);
+        add_toggle_action!(RevealAnimationAction, Msg::ToggleRevealAnimation);
+        add_toggle_action!(RevealHighlightAction, Msg::ToggleRevealHighlight);
+        add_toggle_action!(AutoPauseAction, Msg::ToggleAutoPause);
+        add_toggle_action!(StartHintAction, Msg::ToggleStartHint);
+        add_toggle_action!(SmartClickAction, Msg::ToggleSmartClick);
+        add_toggle_action!(VerboseAnnouncementsAction, Msg::ToggleVerboseAnnouncements);
+        add_toggle_action!(OpenAllZerosAction, Msg::ToggleOpenAllZeros);
+        add_toggle_action!(FirstClickOpeningAction, Msg::ToggleFirstClickOpening);
+        // On by default like the original game
+        add_toggle_action!(TimerHeadStartAction, Msg::ToggleTimerHeadStart, true);
+        add_toggle_action!(GuardUnsafeChordsAction, Msg::ToggleGuardUnsafeChords);
+        // Adds question marks to the right-click cycle
+        add_toggle_action!(QuestionMarksAction, Msg::ToggleQuestionMarks);
+        add_toggle_action!(WorriedFaceAction, Msg::ToggleWorriedFace);
+        add_toggle_action!(PatternHintsAction, Msg::TogglePatternHints);
+        add_toggle_action!(MineOddsAction, Msg::ToggleMineOdds);
+        add_toggle_action!(SafeCellsBadgeAction, Msg::ToggleSafeCellsBadge);
+        add_toggle_action!(
+            ResultDialogAction,
+            Msg::ToggleResultDialog,
+            settings.result_dialog
+        );
+
+        // Cell size option, 0 picks the size automatically
+        let sender_clone = sender.clone();
+        group.add_action(
+            RelmAction::<CellSizeAction>::new_stateful_with_target_value(
+                &settings.cell_size.unwrap_or(0),
+                move |_, state, size| {
+                    *state = size;
+                    sender_clone.input(Msg::SetCellSize((size > 0).then_some(size)));
+                },
+            ),
+        );
+
+        // Zoom actions
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<ZoomInAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::Zoom(ZOOM_STEP));
+        }));
+        relm4::main_application().set_accels_for_action(
+            "win.zoom-in",
+            &["<Control>plus", "<Control>equal", "<Control>KP_Add"],
+        );
+
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<ZoomOutAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::Zoom(-ZOOM_STEP));
+        }));
+        relm4::main_application()
+            .set_accels_for_action("win.zoom-out", &["<Control>minus", "<Control>KP_Subtract"]);
+        relm4::main_application().set_accels_for_action("win.cell-size(0)", &["<Control>0"]);
+
+        // Theme option
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<ThemeAction>::new_stateful_with_target_value(
+            &settings.theme.id(),
+            move |_, state, id| {
+                if let Some(theme) = Theme::from_id(id) {
+                    *state = id;
+                    sender_clone.input(Msg::SetTheme(theme));
+                }
+            },
+        ));
+
+        // Colorblind numbers option
+        let sender_clone = sender.clone();
+        group.add_action(
+            RelmAction::<NumberPaletteAction>::new_stateful_with_target_value(
+                &settings.number_palette.id(),
+                move |_, state, id| {
+                    if let Some(palette) = NumberPalette::from_id(id) {
+                        *state = id;
+                        sender_clone.input(Msg::SetNumberPalette(palette));
+                    }
+                },
+            ),
+        );
+
+        // Auto-solve action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<AutoSolveAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::AutoSolve);
+        }));
+
+        // Demo action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<DemoAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::StartDemo);
+        }));
+
         // Exit action
-        let window_clone = window.clone();
+        let sender_clone = sender.clone();
         group.add_action(RelmAction::<ExitAction>::new_stateless(move |_| {
-            gtk::Window::close(&window_clone);
+            sender_clone.input(Msg::Quit);
+        }));
+        relm4::main_application().set_accels_for_action("win.exit", &["<Control>q"]);
+
+        // Statistics action
+        let sender_clone = sender.clone();
+        group.add_action(RelmAction::<StatisticsAction>::new_stateless(move |_| {
+            sender_clone.input(Msg::ShowStatistics);
         }));
 
         group.add_action(RelmAction::<AboutAction>::new_stateless(move |_| {
@@ -472,6 +2676,14 @@ impl App {
 
     #[inline]
     fn leftbutton_pressed(&mut self) {
+        self.stop_demo();
+
+        // A click during the reveal animation only skips it
+        if self.finish_reveal_animation() {
+            self.mouse_tracker.skipped_click = true;
+            return;
+        }
+
         self.mouse_tracker.lbutton_state = MouseState::Pressed;
 
         if let Some(cell_pos) = self.mouse_tracker.mouse_cell {
@@ -481,17 +2693,31 @@ impl App {
 
     #[inline]
     fn leftbutton_released(&mut self) {
+        if std::mem::take(&mut self.mouse_tracker.skipped_click) {
+            return;
+        }
+
         self.mouse_tracker.lbutton_state = MouseState::Released;
         self.mouse_tracker.mbutton_state = MouseState::Released;
 
         if let Some(cell_pos) = self.mouse_tracker.mouse_cell {
             self.deactivate_cell(cell_pos, &MouseButton::Left);
-            self.handle_reveal(cell_pos);
+            if self.smart_click {
+                self.handle_primary_action(cell_pos);
+            } else {
+                self.handle_reveal(cell_pos);
+            }
         }
     }
 
     #[inline]
     fn rightbutton_pressed(&mut self) {
+        self.stop_demo();
+
+        if self.finish_reveal_animation() {
+            return;
+        }
+
         if let Some(cell_pos) = self.mouse_tracker.mouse_cell.take() {
             self.deactivate_cell(cell_pos, &MouseButton::Middle);
             self.handle_flag(cell_pos);
@@ -503,6 +2729,13 @@ impl App {
     }
 
     fn middlebutton_pressed(&mut self) {
+        self.stop_demo();
+
+        if self.finish_reveal_animation() {
+            self.mouse_tracker.skipped_click = true;
+            return;
+        }
+
         self.mouse_tracker.mbutton_state = MouseState::Pressed;
 
         if let Some(cell_pos) = self.mouse_tracker.mouse_cell {
@@ -511,15 +2744,24 @@ impl App {
     }
 
     fn middlebutton_released(&mut self) {
+        if std::mem::take(&mut self.mouse_tracker.skipped_click) {
+            return;
+        }
+
         self.mouse_tracker.mbutton_state = MouseState::Released;
         self.mouse_tracker.lbutton_state = MouseState::Released;
 
         if let Some(cell_pos) = self.mouse_tracker.mouse_cell.take() {
             self.deactivate_cell(cell_pos, &MouseButton::Middle);
 
-            if let Ok(chord_cells) = self.game_state.chording(cell_pos) {
-                self.reveal_cells(&chord_cells);
-            }
+            self.handle_chord(cell_pos);
+        }
+    }
+
+    fn handle_chord(&mut self, cell_pos: CellPosition) {
+        match self.game_state.chording(cell_pos) {
+            Ok(chord_cells) => self.reveal_cells(&chord_cells, cell_pos),
+            Err(err) => self.report_error(&err),
         }
     }
 
@@ -527,10 +2769,8 @@ impl App {
     fn activate_cell(&self, cell_pos: CellPosition) {
         let board_size = self.game_state.difficulty().board_size;
 
-        if let Ok(cell_content) = self.game_state.display_cell(cell_pos) {
-            if cell_content == "🚩" {
-                return;
-            }
+        if self.game_state.is_flagged(cell_pos).unwrap_or(false) {
+            return;
         }
 
         let index = cell_pos.to_index(board_size);
@@ -544,11 +2784,9 @@ impl App {
     #[inline]
     fn activate_adjacent_cells(&self, cell_pos: CellPosition) {
         for adj_pos in self.game_state.adjacent_positions(cell_pos) {
-            if let Ok(cell_content) = self.game_state.display_cell(adj_pos) {
-                if cell_content != "🚩" {
-                    let adj_index = adj_pos.to_index(self.game_state.difficulty().board_size);
-                    self.cells.send(adj_index, ButtonMsg::Activate);
-                }
+            if matches!(self.game_state.is_flagged(adj_pos), Ok(false)) {
+                let adj_index = adj_pos.to_index(self.game_state.difficulty().board_size);
+                self.cells.send(adj_index, ButtonMsg::Activate);
             }
         }
     }
@@ -566,11 +2804,217 @@ impl App {
         }
     }
 
+    fn key_pressed(&mut self, key: gdk::Key) {
+        match key.to_lower() {
+            HIDE_FLAGS_KEY => self.set_flags_hidden(true),
+            FLAG_KEY => self.handle_mark_key(Mark::Flag),
+            QUESTION_KEY => self.handle_mark_key(Mark::Question),
+            SMART_CHORD_KEY => self.handle_smart_chord_key(),
+            SWEEP_KEY => self.handle_sweep_key(),
+            REVEAL_KEY | REVEAL_SPACE_KEY => self.handle_reveal_key(),
+            _ => (),
+        }
+    }
+
+    /// Flags or chords around the number under the mouse, when that is safe.
+    fn handle_smart_chord_key(&mut self) {
+        if let Some(cell_pos) = self.mouse_tracker.mouse_cell {
+            self.handle_smart_chord(cell_pos);
+        }
+    }
+
+    fn handle_smart_chord(&mut self, cell_pos: CellPosition) {
+        match self.game_state.chording_smart(cell_pos) {
+            Ok(RevealResult::Continue(0)) => {
+                self.sound.play_flag();
+                let adjacent: Vec<CellPosition> =
+                    self.game_state.adjacent_positions(cell_pos).collect();
+                for adj_pos in adjacent {
+                    self.redraw_cell(adj_pos);
+                }
+            }
+            Ok(RevealResult::CantReveal(_)) => (),
+            Ok(smart_result) => self.reveal_cells(&smart_result, cell_pos),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Reveals the focused cell and tells screen readers what it showed.
+    fn handle_reveal_key(&mut self) {
+        if self.game_state.status().is_over() {
+            return;
+        }
+
+        let cell_pos = self.focused_cell;
+        match self.game_state.reveal_cell(cell_pos) {
+            Ok(reveal_result) => {
+                self.announce_reveal(&reveal_result, cell_pos);
+                self.reveal_cells(&reveal_result, cell_pos);
+            }
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Moves the keyboard focus by `dx` columns and `dy` rows, stopping at
+    /// the board edges. The GTK focus follows, so screen readers read the
+    /// cell out.
+    fn move_focus(&mut self, dx: isize, dy: isize) {
+        let (width, height) = self.game_state.difficulty().board_size;
+        let cell_pos = CellPosition::new(
+            self.focused_cell.x.saturating_add_signed(dx).min(width - 1),
+            self.focused_cell
+                .y
+                .saturating_add_signed(dy)
+                .min(height - 1),
+        );
+        if cell_pos == self.focused_cell {
+            return;
+        }
+
+        let board_size = (width, height);
+        self.cells
+            .send(self.focused_cell.to_index(board_size), ButtonMsg::Unfocus);
+        self.cells
+            .send(cell_pos.to_index(board_size), ButtonMsg::Focus);
+        self.focused_cell = cell_pos;
+
+        let grid = self.cells.widget();
+        if let Some(cell) = grid.child_at(
+            i32::try_from(cell_pos.x).unwrap_or(i32::MAX),
+            i32::try_from(cell_pos.y).unwrap_or(i32::MAX),
+        ) {
+            cell.grab_focus();
+        }
+    }
+
+    /// Puts the keyboard focus on the center cell, as a new game starts.
+    fn focus_center(&mut self) {
+        let (width, height) = self.game_state.difficulty().board_size;
+        self.focused_cell = CellPosition::new(width / 2, height / 2);
+
+        // The board may have been resized, so the old focus is cleared everywhere
+        self.cells.broadcast(ButtonMsg::Unfocus);
+        self.cells.send(
+            self.focused_cell.to_index((width, height)),
+            ButtonMsg::Focus,
+        );
+    }
+
+    /// Words the outcome of a keyboard reveal for screen readers, tersely or
+    /// with the cell name and cascade size.
+    fn announce_reveal(&mut self, reveal_result: &RevealResult, cell_pos: CellPosition) {
+        let place = cell_name(cell_pos);
+        let verbose = self.verbose_announcements;
+
+        let mut announcement = match reveal_result {
+            RevealResult::Continue(1) => {
+                let content = match self.game_state.last_reveal_content() {
+                    Some(CellContent::Empty) | None => "empty".to_string(),
+                    Some(content) => content.as_number().to_string(),
+                };
+                if verbose {
+                    format!("Revealed {content} at {place}")
+                } else {
+                    format!("Revealed {content}")
+                }
+            }
+            RevealResult::Continue(count) if verbose => {
+                format!("Opened region of {count} cells, cursor now at {place}")
+            }
+            RevealResult::Continue(count) => format!("Opened {count} cells"),
+            RevealResult::GameOver(_) if verbose => format!("Mine at {place}, game over"),
+            RevealResult::GameOver(_) => "Mine, game over".to_string(),
+            RevealResult::CantReveal(CantReveal::Flagged) => "Flagged".to_string(),
+            RevealResult::CantReveal(_) => "Already revealed".to_string(),
+        };
+        if self.game_state.status().is_won() {
+            announcement.push_str(", you win");
+        }
+
+        self.announcement = announcement;
+    }
+
+    /// Chords every satisfied number, to clear the board once it is flagged.
+    fn handle_sweep_key(&mut self) {
+        // Reveal animations spread from the mouse, or the corner without it
+        let origin = self
+            .mouse_tracker
+            .mouse_cell
+            .unwrap_or(CellPosition::new(0, 0));
+
+        match self.game_state.sweep() {
+            Ok(RevealResult::CantReveal(_)) => (),
+            Ok(sweep_result) => self.reveal_cells(&sweep_result, origin),
+            Err(err) => self.report_error(&err),
+        }
+    }
+
+    /// Puts the mark on the cell under the mouse, or the focused cell when the
+    /// mouse is off the board, or takes it off if the cell already has it.
+    /// Marks outside the mark cycle of the options can't be put.
+    fn handle_mark_key(&mut self, mark: Mark) {
+        let cell_pos = self.mouse_tracker.mouse_cell.unwrap_or(self.focused_cell);
+
+        let mark = match self.game_state.mark(cell_pos) {
+            Ok(current) if current == mark => Mark::None,
+            Ok(_) => mark,
+            Err(err) => {
+                self.report_error(&err);
+                return;
+            }
+        };
+        // Without Marks (?) a question mark can only be taken off
+        if !self.game_state.options().mark_cycle.contains(mark) {
+            return;
+        }
+
+        self.handle_set_mark(cell_pos, mark);
+    }
+
+    /// Puts the mark on the cell, whatever mark it had.
+    fn handle_set_mark(&mut self, cell_pos: CellPosition, mark: Mark) {
+        match self.game_state.set_mark(cell_pos, mark) {
+            Ok(true) => {
+                self.sound.play_flag();
+                self.redraw_cell(cell_pos);
+            }
+            Ok(false) => (),
+            Err(err) => self.report_error(&err),
+        }
+
+        if let Some(auto_chord_result) = self.game_state.take_auto_chord_result() {
+            self.reveal_cells(&auto_chord_result, cell_pos);
+        }
+    }
+
+    fn key_released(&mut self, key: gdk::Key) {
+        if key.to_lower() == HIDE_FLAGS_KEY {
+            self.set_flags_hidden(false);
+        }
+    }
+
+    /// Temporarily hides the flag images so the cells below can be seen.
+    fn set_flags_hidden(&mut self, hidden: bool) {
+        // Key auto-repeat sends the same press over and over
+        if self.flags_hidden == hidden {
+            return;
+        }
+
+        self.flags_hidden = hidden;
+        self.cells.broadcast(ButtonMsg::HideFlag(hidden));
+    }
+
     fn emoji_status(&self) -> String {
-        if self.mouse_tracker.is_pressed() && !self.game_state.status().is_over() {
+        let status = self.game_state.status();
+
+        if self.game_state.is_paused() {
+            "😴".to_owned()
+        } else if self.mouse_tracker.is_pressed() && !status.is_over() {
             "😯".to_owned()
+        } else if self.worried_face && !status.is_over() && self.game_state.is_over_flagged() {
+            "😟".to_owned()
         } else {
-            self.game_state.status().to_string()
+            status.to_string()
         }
     }
 }
@@ -579,6 +3023,7 @@ struct MouseTracker {
     mouse_cell: Option<CellPosition>,
     lbutton_state: MouseState,
     mbutton_state: MouseState,
+    skipped_click: bool,
 }
 
 impl MouseTracker {
@@ -587,6 +3032,7 @@ impl MouseTracker {
             mouse_cell: None,
             lbutton_state: MouseState::Released,
             mbutton_state: MouseState::Released,
+            skipped_click: false,
         }
     }
 
@@ -609,3 +3055,184 @@ enum MouseButton {
     Middle,
     // Right,
 }
+
+/// Revealed cells waiting to be shown, one wave at a time.
+struct RevealAnimation {
+    enabled: bool,
+    waves: VecDeque<Vec<(usize, String)>>,
+    timer: Option<gtk::glib::SourceId>,
+}
+
+impl RevealAnimation {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            waves: VecDeque::new(),
+            timer: None,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.timer.is_some() || !self.waves.is_empty()
+    }
+
+    fn cancel(&mut self) {
+        self.waves.clear();
+        if let Some(timer) = self.timer.take() {
+            timer.remove();
+        }
+    }
+}
+
+/// Cells revealed by the latest action, highlighted for a moment.
+struct RevealHighlight {
+    enabled: bool,
+    batch: u64,
+    cells: Vec<usize>,
+    clear_scheduled: bool,
+}
+
+impl RevealHighlight {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            batch: 0,
+            cells: Vec::new(),
+            clear_scheduled: false,
+        }
+    }
+}
+
+/// The toast celebrating a first click that opened a large part of the board.
+#[derive(Default)]
+struct LuckyToast {
+    visible: bool,
+    /// Whether the first click of the game was already looked at.
+    checked: bool,
+}
+
+/// The tick that redraws the timer. Ticks from before the latest reset have
+/// an older `id` and are ignored.
+#[derive(Default)]
+struct Clock {
+    id: u64,
+    scheduled: bool,
+}
+
+/// The replay on the board. Its next event is scheduled for when the game
+/// clock reaches it. Steps from before the latest replay started or stopped
+/// have an older `id` and are ignored.
+#[derive(Default)]
+struct ReplayPlayback {
+    id: u64,
+    scheduled: bool,
+    events: VecDeque<ReplayEvent>,
+    /// The player's options, given back when the replayed game is left.
+    /// `None` unless a replay is on the board.
+    player_options: Option<GameOptions>,
+}
+
+impl ReplayPlayback {
+    /// Returns whether a replay is on the board, played or done playing.
+    const fn is_watching(&self) -> bool {
+        self.player_options.is_some()
+    }
+}
+
+/// The 3BV and speed of a finished game, shown in a popover when it ends.
+#[derive(Default)]
+struct EndStats {
+    open: bool,
+    three_bv: Option<usize>,
+    /// Only known for won games.
+    three_bv_per_second: Option<f64>,
+}
+
+/// A message shown in the status bar until it times out.
+#[derive(Default)]
+struct StatusMessage {
+    text: Option<String>,
+    error: bool,
+    id: u64,
+    clear_scheduled: bool,
+}
+
+/// Where the best times are saved, in the user's config directory.
+fn best_times_path() -> PathBuf {
+    gtk::glib::user_config_dir()
+        .join(CONFIG_DIR_NAME)
+        .join(BEST_TIMES_FILE)
+}
+
+/// Where the statistics are saved, next to the best times.
+fn statistics_path() -> PathBuf {
+    gtk::glib::user_config_dir()
+        .join(CONFIG_DIR_NAME)
+        .join(STATISTICS_FILE)
+}
+
+/// Where the settings are saved, next to the best times.
+#[must_use]
+pub fn settings_path() -> PathBuf {
+    gtk::glib::user_config_dir()
+        .join(CONFIG_DIR_NAME)
+        .join(SETTINGS_FILE)
+}
+
+/// Formats a number of seconds as hours, minutes and seconds, like 1:02:03,
+/// or minutes and seconds under an hour.
+fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Names a cell like a spreadsheet does: column letters then row number, so
+/// the top left cell is A1.
+fn cell_name(pos: CellPosition) -> String {
+    let mut column = String::new();
+    let mut remaining = pos.x + 1;
+    while remaining > 0 {
+        remaining -= 1;
+        column.insert(
+            0,
+            char::from(b'A' + u8::try_from(remaining % 26).unwrap_or(0)),
+        );
+        remaining /= 26;
+    }
+
+    format!("{column}{}", pos.y + 1)
+}
+
+/// Returns whether `message` plays on the board or moves around it.
+const fn is_board_input(message: &Msg) -> bool {
+    matches!(
+        message,
+        Msg::TrackMouse(..)
+            | Msg::LeftButtonPressed
+            | Msg::LeftButtonReleased
+            | Msg::RightButtonPressed
+            | Msg::MiddleButtonPressed
+            | Msg::MiddleButtonReleased
+            | Msg::KeyPressed(_)
+            | Msg::MoveFocus(..)
+            | Msg::Undo
+            | Msg::Hint
+            | Msg::DemoMove
+            | Msg::AutoSolve
+    )
+}
+
+/// Returns the columns and rows an arrow key moves the keyboard focus by.
+fn arrow_direction(key: gdk::Key) -> Option<(isize, isize)> {
+    match key {
+        gdk::Key::Left | gdk::Key::KP_Left => Some((-1, 0)),
+        gdk::Key::Right | gdk::Key::KP_Right => Some((1, 0)),
+        gdk::Key::Up | gdk::Key::KP_Up => Some((0, -1)),
+        gdk::Key::Down | gdk::Key::KP_Down => Some((0, 1)),
+        _ => None,
+    }
+}
@@ -0,0 +1,131 @@
+use crate::game::models::cell::CellContent;
+use gtk::prelude::MediaStreamExt;
+use gtk::{gio, glib};
+use std::f64::consts::TAU;
+
+const SAMPLE_RATE: u32 = 22_050;
+const TONE_MILLIS: u32 = 60;
+const TONE_AMPLITUDE: f64 = 0.3;
+const BASE_FREQUENCY: f64 = 440.0;
+const FLAG_WAV: &[u8] = include_bytes!("../assets/sounds/flag.wav");
+const WIN_WAV: &[u8] = include_bytes!("../assets/sounds/win.wav");
+const EXPLOSION_WAV: &[u8] = include_bytes!("../assets/sounds/explosion.wav");
+
+/// Plays the game sound effects. Reveal tones are synthesized in memory, the
+/// other sounds are bundled WAV clips. All are handed to GTK's media backend, so playback never blocks the UI thread and a
+/// missing audio device just results in silence.
+pub struct SoundPlayer {
+    enabled: bool,
+    // The stream must outlive its playback, so the latest one is kept around.
+    stream: Option<gtk::MediaFile>,
+}
+
+impl SoundPlayer {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            enabled: false,
+            stream: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.stream = None;
+        }
+    }
+
+    /// Plays the reveal tone. The pitch goes up a whole tone for each adjacent
+    /// mine, so a blank opening is the lowest note and an 8 the highest.
+    pub fn play_reveal(&mut self, content: CellContent) {
+        if !self.enabled || matches!(content, CellContent::Mine) {
+            return;
+        }
+
+        let frequency = BASE_FREQUENCY * 2f64.powf(f64::from(content.as_number()) / 6.0);
+        self.play(&glib::Bytes::from_owned(tone_wav(frequency)));
+    }
+
+    /// Plays a low tone when a click can't reveal a flagged cell.
+    pub fn play_blocked(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        self.play(&glib::Bytes::from_owned(tone_wav(BASE_FREQUENCY / 2.0)));
+    }
+
+    /// Plays a click when a mark is put on or taken off a cell.
+    pub fn play_flag(&mut self) {
+        self.play_clip(FLAG_WAV);
+    }
+
+    /// Plays a jingle when the game is won.
+    pub fn play_win(&mut self) {
+        self.play_clip(WIN_WAV);
+    }
+
+    /// Plays an explosion when a mine is revealed.
+    pub fn play_explosion(&mut self) {
+        self.play_clip(EXPLOSION_WAV);
+    }
+
+    fn play_clip(&mut self, wav: &'static [u8]) {
+        if !self.enabled {
+            return;
+        }
+
+        self.play(&glib::Bytes::from_static(wav));
+    }
+
+    fn play(&mut self, bytes: &glib::Bytes) {
+        let input = gio::MemoryInputStream::from_bytes(bytes);
+        let media = gtk::MediaFile::for_input_stream(&input);
+        media.play();
+
+        self.stream = Some(media);
+    }
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a short mono 16-bit PCM WAV with a sine tone fading out.
+fn tone_wav(frequency: f64) -> Vec<u8> {
+    let sample_count = SAMPLE_RATE * TONE_MILLIS / 1000;
+    let data_len = sample_count * 2;
+
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    for i in 0..sample_count {
+        let time = f64::from(i) / f64::from(SAMPLE_RATE);
+        let fade = 1.0 - f64::from(i) / f64::from(sample_count);
+        let sample = (time * frequency * TAU).sin() * fade * TONE_AMPLITUDE;
+        #[allow(clippy::cast_possible_truncation)]
+        let sample = (sample * f64::from(i16::MAX)) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    wav
+}
@@ -1,30 +1,113 @@
-use gtk::prelude::{GridExt, WidgetExt};
+use gtk::prelude::{AccessibleExt, Cast, GridExt, LayoutManagerExt, WidgetExt};
 use relm4::{
     factory::positions::GridPosition,
-    prelude::{DynamicIndex, FactoryComponent},
+    prelude::{DynamicIndex, FactoryComponent, FactoryVecDeque},
     FactorySender,
 };
 
 use crate::game::models::cell::CellPosition;
 
-const CELL_SIZE: i32 = 20;
 const FLAG_ICON: &[u8] = include_bytes!("../../assets/flag.png");
+// One distinct shape per number, so they can be told apart without colors.
+const NUMBER_GLYPHS: [&str; 8] = ["●", "▲", "■", "◆", "★", "✚", "⬟", "✖"];
+// Share of the cell, in percent, that numbers, emoji and the flag take up, so
+// they fit inside the borders at any cell size.
+const GLYPH_SCALE_PERCENT: i32 = 55;
+// Overlays hold a few characters, so they get a smaller share.
+const OVERLAY_SCALE_PERCENT: i32 = 30;
 
 pub struct ButtonCell {
     pub label: String,
     pub css_classes: Vec<String>,
     pub position: CellPosition,
+    pub flag_hidden: bool,
+    pub number_glyphs: bool,
+    pub cell_size: i32,
+    /// Whether the keyboard focus is on the cell.
+    pub focused: bool,
+    /// Faint text shown while the cell is hidden, like its mine odds.
+    pub overlay: Option<String>,
 }
 
 impl ButtonCell {
     #[must_use]
-    pub fn new(pos: CellPosition) -> Self {
+    pub fn new(pos: CellPosition, cell_size: i32) -> Self {
         Self {
             label: String::new(),
             css_classes: vec!["square-button".to_string()],
             position: pos,
+            flag_hidden: false,
+            number_glyphs: false,
+            cell_size,
+            focused: false,
+            overlay: None,
         }
     }
+
+    /// Returns the size, in pixels, of what the cell shows.
+    const fn glyph_size(&self) -> i32 {
+        self.cell_size * GLYPH_SCALE_PERCENT / 100
+    }
+
+    /// Sizes the label text to the cell, so emoji don't get clipped on small
+    /// cells.
+    fn glyph_attributes(&self) -> gtk::pango::AttrList {
+        let size = if self.shows_overlay() {
+            self.cell_size * OVERLAY_SCALE_PERCENT / 100
+        } else {
+            self.glyph_size()
+        };
+        let attributes = gtk::pango::AttrList::new();
+        attributes.insert(gtk::pango::AttrSize::new_size_absolute(
+            size * gtk::pango::SCALE,
+        ));
+
+        attributes
+    }
+
+    fn shows_flag(&self) -> bool {
+        self.label == "🚩" && !self.flag_hidden
+    }
+
+    fn shows_overlay(&self) -> bool {
+        self.label.is_empty() && self.overlay.is_some()
+    }
+
+    fn display_label(&self) -> String {
+        if self.label == "🚩" {
+            return String::new();
+        }
+        if self.label.is_empty() {
+            return self.overlay.clone().unwrap_or_default();
+        }
+
+        if self.number_glyphs {
+            if let Ok(number @ 1..=8) = self.label.parse::<usize>() {
+                return format!("{}{}", NUMBER_GLYPHS[number - 1], self.label);
+            }
+        }
+
+        self.label.clone()
+    }
+
+    /// Describes the cell for screen readers.
+    fn accessible_label(&self) -> String {
+        let state = match self.label.as_str() {
+            "" => "hidden".to_string(),
+            " " => "revealed empty".to_string(),
+            "🚩" => "flagged".to_string(),
+            "❓" => "question mark".to_string(),
+            "💣" => "mine".to_string(),
+            "❌" => "wrong flag".to_string(),
+            number => format!("revealed {number}"),
+        };
+
+        format!(
+            "Column {}, row {}, {state}",
+            self.position.x + 1,
+            self.position.y + 1
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,8 +115,15 @@ pub enum ButtonMsg {
     Display(String),
     Reset,
     AddCssClass(String),
+    RemoveCssClass(String),
     Activate,
     Deactivate,
+    HideFlag(bool),
+    SetNumberGlyphs(bool),
+    SetCellSize(i32),
+    SetOverlay(Option<String>),
+    Focus,
+    Unfocus,
 }
 
 impl relm4::factory::Position<GridPosition, DynamicIndex> for ButtonCell {
@@ -41,9 +131,10 @@ impl relm4::factory::Position<GridPosition, DynamicIndex> for ButtonCell {
         let x = self.position.x;
         let y = self.position.y;
 
+        // Board::validate_difficulty keeps the dimensions within i32
         GridPosition {
-            column: i32::try_from(x).expect("This conversion should always succeed. (C02)"),
-            row: i32::try_from(y).expect("This conversion should always succeed. (C01)"),
+            column: i32::try_from(x).unwrap_or(i32::MAX),
+            row: i32::try_from(y).unwrap_or(i32::MAX),
             width: 1,
             height: 1,
         }
@@ -62,38 +153,64 @@ impl FactoryComponent for ButtonCell {
 
     view! {
         #[root]
-        gtk::Box {
-            set_can_focus: false,
+        gtk::Box::builder().accessible_role(gtk::AccessibleRole::GridCell).build() {
+            // Focusable so screen readers can walk the board
+            set_focusable: true,
+            add_css_class: "cell",
+            #[watch]
+            set_class_active: ("keyboard-focus", self.focused),
+            #[watch]
+            update_property: &[gtk::accessible::Property::Label(&self.accessible_label())],
             set_hexpand: false,
             set_vexpand: false,
-            set_width_request: CELL_SIZE,
-            set_height_request: CELL_SIZE,
+            #[watch]
+            set_width_request: self.cell_size,
+            #[watch]
+            set_height_request: self.cell_size,
             set_can_target: false,
 
             gtk::Label {
                 set_can_focus: false,
                 set_hexpand: true,
                 set_vexpand: true,
-                set_width_request: CELL_SIZE,
-                set_height_request: CELL_SIZE,
+                #[watch]
+                set_width_request: self.cell_size,
+                #[watch]
+                set_height_request: self.cell_size,
                 set_can_target: false,
 
                 #[watch]
                 set_css_classes: &self.css_classes.iter().map(std::string::String::as_str).collect::<Vec<&str>>(),
                 #[watch]
-                set_label: &self.label,
+                set_class_active: ("cell-overlay", self.shows_overlay()),
+                #[watch]
+                set_label: &self.display_label(),
+                #[watch]
+                set_attributes: Some(&self.glyph_attributes()),
                 #[watch]
-                set_visible: self.label != "🚩",
+                set_visible: !self.shows_flag(),
 
             },
-            #[name(test_grid)]
-             gtk::Grid {
+            gtk::Grid {
                 #[watch]
-                set_visible: self.label == "🚩",
+                set_visible: self.shows_flag(),
                 set_hexpand: true,
                 set_vexpand: true,
                 #[watch]
                 set_css_classes: &self.css_classes.iter().map(std::string::String::as_str).collect::<Vec<&str>>(),
+
+                // Images keep their aspect ratio within the pixel size
+                attach[0, 0, 1, 1] = &gtk::Image {
+                    set_paintable: Some(&gtk::gdk::Texture::for_pixbuf(
+                        &gtk::gdk_pixbuf::Pixbuf::from_read(FLAG_ICON).expect("Failed to create pixbuf"),
+                    )),
+                    set_hexpand: true,
+                    set_vexpand: true,
+                    set_halign: gtk::Align::Center,
+                    set_valign: gtk::Align::Center,
+                    #[watch]
+                    set_pixel_size: self.glyph_size(),
+                },
             }
         }
     }
@@ -109,24 +226,16 @@ impl FactoryComponent for ButtonCell {
         _returned_widget: &gtk::Widget,
         _sender: FactorySender<Self>,
     ) -> Self::Widgets {
-        let button = view_output!();
-
-        let picture = gtk::Image::from_paintable(Some(&gtk::gdk::Texture::for_pixbuf(
-            &gtk::gdk_pixbuf::Pixbuf::from_read(FLAG_ICON).expect("Failed to create pixbuf"),
-        )));
-        picture.set_hexpand(true);
-        picture.set_vexpand(true);
-        picture.set_halign(gtk::Align::Center);
-        picture.set_valign(gtk::Align::Center);
+        let widgets = view_output!();
 
-        button.test_grid.attach(&picture, 0, 0, 1, 1);
-
-        button
+        widgets
     }
 
     fn update(&mut self, msg: Self::Input, _sender: FactorySender<Self>) -> Self::CommandOutput {
         match msg {
             ButtonMsg::Display(label) => {
+                // A question mark can be taken off without a reset
+                self.css_classes.retain(|c| c != "question-mark");
                 match label.as_str() {
                     "1" => self.css_classes.push("number-one".to_string()),
                     "2" => self.css_classes.push("number-two".to_string()),
@@ -136,6 +245,7 @@ impl FactoryComponent for ButtonCell {
                     "6" => self.css_classes.push("number-six".to_string()),
                     "7" => self.css_classes.push("number-seven".to_string()),
                     "8" => self.css_classes.push("number-eight".to_string()),
+                    "❓" => self.css_classes.push("question-mark".to_string()),
                     _ => {}
                 }
                 self.label = label;
@@ -143,10 +253,67 @@ impl FactoryComponent for ButtonCell {
             ButtonMsg::Reset => {
                 self.label = String::new();
                 self.css_classes = vec!["square-button".to_string()];
+                self.overlay = None;
             }
             ButtonMsg::AddCssClass(class) => self.css_classes.push(class),
+            ButtonMsg::RemoveCssClass(class) => self.css_classes.retain(|c| *c != class),
             ButtonMsg::Activate => self.css_classes.push("active".to_string()),
             ButtonMsg::Deactivate => self.css_classes.retain(|c| c != "active"),
+            ButtonMsg::HideFlag(hidden) => self.flag_hidden = hidden,
+            ButtonMsg::SetNumberGlyphs(enabled) => self.number_glyphs = enabled,
+            ButtonMsg::SetCellSize(size) => self.cell_size = size,
+            ButtonMsg::SetOverlay(overlay) => self.overlay = overlay,
+            ButtonMsg::Focus => self.focused = true,
+            ButtonMsg::Unfocus => self.focused = false,
+        }
+    }
+}
+
+/// Resizes the grid of `cells` to `board_size`. Only the difference is added
+/// or dropped at the end, the cells kept are moved to their new position, as
+/// rebuilding thousands of widgets is what made difficulty changes lag.
+pub fn resize_grid(
+    cells: &mut FactoryVecDeque<ButtonCell>,
+    board_size: (usize, usize),
+    cell_size: i32,
+) {
+    let new_len = board_size.0 * board_size.1;
+    let position_of = |index: usize| CellPosition::from_index(index, board_size);
+
+    let mut cells_guard = cells.guard();
+    while cells_guard.len() > new_len {
+        cells_guard.pop_back();
+    }
+    for index in 0..cells_guard.len() {
+        if let Some(cell) = cells_guard.get_mut(index) {
+            cell.position = position_of(index);
+        }
+    }
+    while cells_guard.len() < new_len {
+        let index = cells_guard.len();
+        cells_guard.push_back(ButtonCell::new(position_of(index), cell_size));
+    }
+    cells_guard.drop();
+
+    // The factory only places widgets when they are added, so the kept
+    // ones are moved by hand. Cells are only pushed and popped at the
+    // back, so the grid children are in index order.
+    let grid = cells.widget();
+    let Some(layout) = grid.layout_manager() else {
+        return;
+    };
+    let mut child = grid.first_child();
+    let mut index = 0;
+    while let Some(widget) = child {
+        if let Ok(grid_child) = layout
+            .layout_child(&widget)
+            .downcast::<gtk::GridLayoutChild>()
+        {
+            let position = position_of(index);
+            grid_child.set_column(i32::try_from(position.x).unwrap_or(i32::MAX));
+            grid_child.set_row(i32::try_from(position.y).unwrap_or(i32::MAX));
         }
+        child = widget.next_sibling();
+        index += 1;
     }
 }
@@ -8,8 +8,14 @@ pub enum GameError {
     InvalidMinesCount(usize, (usize, usize)),
     #[error("Invalid cell position: ({0}, {1})")]
     InvalidCellPosition(usize, usize),
+    #[error("Failed to generate a suitable board in {0} attempts")]
+    GenerationFailed(u32),
+    #[error("Malformed board: {0}")]
+    MalformedBoard(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
 }
 
 pub type GameResult<T> = Result<T, GameError>;
@@ -1,2 +1,5 @@
 pub mod models;
+pub mod replay;
+pub mod scores;
+pub mod solver;
 pub mod state;
@@ -1,22 +1,77 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
 
 use crate::error::{GameError, GameResult};
-use crate::game::models::cell::{Cell, CellContent, CellPosition};
-use crate::game::models::game::GameDifficulty;
+use crate::game::models::cell::{Cell, CellContent, CellPosition, CellState};
+use crate::game::models::game::{GameDifficulty, MineDistribution};
+use crate::game::models::options::{GameOptions, GenerationFallback};
+
+/// Number of random cells compared for each mine of a weighted distribution.
+const DISTRIBUTION_CANDIDATES: usize = 4;
+const GRID_MINE: char = '*';
+const GRID_SAFE: char = '.';
+const ASCII_FLAG: char = 'F';
+const ASCII_WRONG_FLAG: char = 'X';
+const ASCII_HIDDEN: char = '#';
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// The symbol drawn for each cell of a text grid.
+type GridSymbols = Vec<(CellPosition, char)>;
 
 #[derive(PartialEq, Eq)]
 pub enum RevealResult {
-    Continue,
+    /// The game goes on, holding the number of cells newly revealed.
+    Continue(usize),
     GameOver(CellPosition),
-    CantReveal,
+    CantReveal(CantReveal),
+}
+
+/// Why a reveal or chord didn't reveal anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CantReveal {
+    AlreadyRevealed,
+    /// The cell has to be unflagged first.
+    Flagged,
+    /// The game is over, or a chord had no hidden cells or too few flags
+    /// around it.
+    NothingToReveal,
+    /// The chord relies on a flag the numbers don't prove, see
+    /// [`GameOptions::guard_unsafe_chords`].
+    UnsafeChord,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagResult {
+    Flagged,
+    /// The cell has no mark anymore.
+    Unflagged,
+    /// The cell went from flagged to question marked.
+    Questioned,
+    /// The cell is revealed, so it can't hold a flag.
+    AlreadyRevealed,
+    /// The game is over, so flags can't change anymore.
+    GameOver,
+}
+
+impl FlagResult {
+    #[must_use]
+    pub const fn is_toggled(self) -> bool {
+        matches!(self, Self::Flagged | Self::Unflagged | Self::Questioned)
+    }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Board {
-    cells: Vec<Vec<Cell>>,
+    /// Cells column after column, see [`CellPosition::to_index`].
+    cells: Vec<Cell>,
     size: (usize, usize),
     mine_positions: HashSet<CellPosition>,
     revealed_count: usize,
     flagged_count: isize,
+    seed: u64,
 }
 
 impl Board {
@@ -24,6 +79,11 @@ impl Board {
     ///
     /// # Arguments
     /// * `difficulty` - Contains board size and number of mines
+    /// * `revealed_cell` - The first cell revealed, never a mine
+    /// * `protected_cells` - More cells kept free of mines, as long as the
+    ///   rest of the board has room for all of them
+    /// * `seed` - Seed of the mine layout, or `None` for a random one. The
+    ///   same seed, difficulty and revealed cell always give the same mines
     ///
     /// # Returns
     /// * `GameResult<Self>` - New game board if parameters are valid
@@ -34,42 +94,339 @@ impl Board {
     pub fn new(
         difficulty: GameDifficulty,
         revealed_cell: CellPosition,
-        flagged_cells: Option<&HashSet<CellPosition>>,
+        protected_cells: Option<&HashSet<CellPosition>>,
+        seed: Option<u64>,
     ) -> GameResult<Self> {
         Self::validate_difficulty(difficulty)?;
 
         let mut board = Self {
-            cells: vec![vec![Cell::default(); difficulty.board_size.1]; difficulty.board_size.0],
+            cells: vec![Cell::default(); difficulty.board_size.0 * difficulty.board_size.1],
             size: difficulty.board_size,
             mine_positions: HashSet::with_capacity(difficulty.mines_count),
             revealed_count: 0,
             flagged_count: 0,
+            seed: seed.unwrap_or_else(|| fastrand::u64(..)),
         };
 
-        board.place_mines(difficulty.mines_count, revealed_cell);
+        let mut excluded: HashSet<CellPosition> = protected_cells
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|pos| board.validate_position(*pos).is_ok())
+            .collect();
+        excluded.insert(revealed_cell);
+        if board.cells.len() - excluded.len() < difficulty.mines_count {
+            excluded = HashSet::from([revealed_cell]);
+        }
+
+        board.place_mines(
+            difficulty.mines_count,
+            &excluded,
+            difficulty.mine_distribution,
+        );
+        board.calculate_adjacent_mines();
+        board.debug_assert_layout(difficulty.mines_count);
+
+        Ok(board)
+    }
+
+    /// Creates a board of `board_size` without mines, which holds the marks
+    /// put before the first click, until the mines are placed on it.
+    #[must_use]
+    pub fn blank(board_size: (usize, usize)) -> Self {
+        Self {
+            cells: vec![Cell::default(); board_size.0 * board_size.1],
+            size: board_size,
+            mine_positions: HashSet::new(),
+            revealed_count: 0,
+            flagged_count: 0,
+            seed: 0,
+        }
+    }
+
+    /// Generates boards until `accept` approves one, trying at most the
+    /// options' retry budget. Returns the board and the number of attempts used.
+    ///
+    /// The first attempt uses `seed` as is, later ones seeds drawn from it,
+    /// so a seeded generation always ends on the same board. With
+    /// [`GameOptions::first_click_opening`], the cells around the revealed
+    /// one are kept free of mines too.
+    ///
+    /// # Errors
+    /// * Returns `GameError::GenerationFailed` if the budget runs out and the
+    ///   fallback is [`GenerationFallback::Fail`]
+    /// * Returns the same errors as [`Board::new`]
+    pub fn generate(
+        difficulty: GameDifficulty,
+        revealed_cell: CellPosition,
+        seed: Option<u64>,
+        options: &GameOptions,
+        mut accept: impl FnMut(&Self) -> bool,
+    ) -> GameResult<(Self, u32)> {
+        let retry_budget = options.generation_retry_budget.max(1);
+        let mut retry_seeds = seed.map(fastrand::Rng::with_seed);
+        let mut attempt_seed = seed;
+        let mut attempts = 0;
+        let opening: Option<HashSet<CellPosition>> = options
+            .first_click_opening
+            .then(|| Self::positions_around(revealed_cell, difficulty.board_size).collect());
+
+        loop {
+            attempts += 1;
+            let board = Self::new(difficulty, revealed_cell, opening.as_ref(), attempt_seed)?;
+            attempt_seed = retry_seeds.as_mut().map(|rng| rng.u64(..));
+
+            if accept(&board) {
+                return Ok((board, attempts));
+            }
+
+            if attempts >= retry_budget {
+                return match options.generation_fallback {
+                    GenerationFallback::UseLastBoard => Ok((board, attempts)),
+                    GenerationFallback::Fail => Err(GameError::GenerationFailed(attempts)),
+                };
+            }
+        }
+    }
+
+    /// Creates a board with mines at the given positions instead of random ones.
+    ///
+    /// # Errors
+    /// * Returns `GameError::InvalidBoardSize` if board size is 0
+    /// * Returns `GameError::InvalidMinesCount` if mines count is 0 or exceeds board capacity
+    /// * Returns `GameError::InvalidCellPosition` if a mine is outside the board
+    pub fn with_mines(
+        board_size: (usize, usize),
+        mine_positions: HashSet<CellPosition>,
+    ) -> GameResult<Self> {
+        GameDifficulty::new(board_size.0, board_size.1, mine_positions.len())?;
+
+        let mut board = Self::blank(board_size);
+
+        let mines_count = mine_positions.len();
+        for mine_pos in mine_positions {
+            board.validate_position(mine_pos)?;
+            board.cell_mut(mine_pos).content = CellContent::Mine;
+            board.mine_positions.insert(mine_pos);
+        }
         board.calculate_adjacent_mines();
+        board.debug_assert_layout(mines_count);
+
+        Ok(board)
+    }
+
+    /// Loads a board from a grid file. Each line is a row of the board, from
+    /// top to bottom, and each character a cell, from left to right: `*` for
+    /// a mine and `.` for a safe cell. Trailing blank lines are ignored.
+    ///
+    /// # Errors
+    /// * Returns `GameError::IoError` if the file can't be read
+    /// * Returns `GameError::MalformedBoard` if rows have different lengths or
+    ///   contain other characters
+    /// * Returns the same errors as [`Board::with_mines`] for invalid layouts
+    pub fn from_grid_file(path: &Path) -> GameResult<Self> {
+        let (size, symbols) = parse_grid(&fs::read_to_string(path)?, |symbol| {
+            matches!(symbol, GRID_MINE | GRID_SAFE)
+        })?;
+        let mine_positions = symbols
+            .into_iter()
+            .filter(|(_, symbol)| *symbol == GRID_MINE)
+            .map(|(pos, _)| pos)
+            .collect();
+
+        Self::with_mines(size, mine_positions)
+    }
+
+    /// Saves the mine layout to a grid file, in the format read by
+    /// [`Board::from_grid_file`].
+    ///
+    /// # Errors
+    /// Returns `GameError::IoError` if the file can't be written
+    pub fn to_grid_file(&self, path: &Path) -> GameResult<()> {
+        let mut grid = String::with_capacity((self.size.0 + 1) * self.size.1);
+
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                grid.push(if self.cells[self.index(CellPosition { x, y })].is_mine() {
+                    GRID_MINE
+                } else {
+                    GRID_SAFE
+                });
+            }
+            grid.push('\n');
+        }
+
+        fs::write(path, grid)?;
+        Ok(())
+    }
+
+    /// Draws the board as text, one line per row from top to bottom: `*` for
+    /// a mine, `F` for a flag on a mine, `X` for a flag on a safe cell, `#`
+    /// for any other hidden cell, and `.` or the number for a revealed cell.
+    /// Hidden mines are drawn too, so the text holds the whole layout and
+    /// [`Board::from_ascii`] reads it back.
+    #[must_use]
+    pub fn to_ascii(&self) -> String {
+        self.draw_ascii(true)
+    }
+
+    /// Draws the board like [`Board::to_ascii`], but as the player sees it:
+    /// hidden mines are drawn as hidden cells and every flag as `F`.
+    #[must_use]
+    pub fn to_player_ascii(&self) -> String {
+        self.draw_ascii(false)
+    }
+
+    fn draw_ascii(&self, hidden_mines: bool) -> String {
+        let mut ascii = String::with_capacity((self.size.0 + 1) * self.size.1);
+
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let cell = &self.cells[self.index(CellPosition { x, y })];
+                ascii.push(if cell.is_flagged() {
+                    if hidden_mines && !cell.is_mine() {
+                        ASCII_WRONG_FLAG
+                    } else {
+                        ASCII_FLAG
+                    }
+                } else if cell.is_mine() && (hidden_mines || cell.is_revealed()) {
+                    GRID_MINE
+                } else if !cell.is_revealed() {
+                    ASCII_HIDDEN
+                } else if cell.is_empty() {
+                    GRID_SAFE
+                } else {
+                    char::from(b'0' + cell.content.as_number())
+                });
+            }
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    /// Reads a board drawn by [`Board::to_ascii`]. Mines come back hidden
+    /// and question marks are lost, so only the layout, the flags and the
+    /// revealed cells survive the trip. Trailing blank lines are ignored.
+    ///
+    /// # Errors
+    /// * Returns `GameError::MalformedBoard` if rows have different lengths,
+    ///   contain other characters, or a revealed number doesn't match the
+    ///   mines around it
+    /// * Returns the same errors as [`Board::with_mines`] for invalid layouts
+    pub fn from_ascii(ascii: &str) -> GameResult<Self> {
+        let (size, symbols) = parse_grid(ascii, |symbol| {
+            matches!(
+                symbol,
+                GRID_MINE | GRID_SAFE | ASCII_FLAG | ASCII_WRONG_FLAG | ASCII_HIDDEN | '1'..='8'
+            )
+        })?;
+        let mine_positions = symbols
+            .iter()
+            .filter(|(_, symbol)| matches!(*symbol, GRID_MINE | ASCII_FLAG))
+            .map(|(pos, _)| *pos)
+            .collect();
+        let mut board = Self::with_mines(size, mine_positions)?;
 
-        if let Some(flagged_cells) = flagged_cells {
-            for flagged_cell in flagged_cells {
-                board.flag(*flagged_cell)?;
+        for (pos, symbol) in symbols {
+            match symbol {
+                GRID_MINE | ASCII_HIDDEN => (),
+                ASCII_FLAG | ASCII_WRONG_FLAG => {
+                    board.flag(pos)?;
+                }
+                _ => {
+                    let number = symbol.to_digit(10).unwrap_or(0);
+                    let expected = board.cells[board.index(pos)].content.as_number();
+                    if number != u32::from(expected) {
+                        return Err(GameError::MalformedBoard(format!(
+                            "cell '{symbol}' at row {}, column {} has {expected} mines around it",
+                            pos.y + 1,
+                            pos.x + 1
+                        )));
+                    }
+                    board.reveal(pos)?;
+                }
             }
         }
 
         Ok(board)
     }
 
+    /// Returns the board mirrored along its main diagonal, so a width x height
+    /// board becomes height x width. Marks and revealed cells move with it.
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        self.remap((self.size.1, self.size.0), |pos| {
+            CellPosition::new(pos.y, pos.x)
+        })
+    }
+
+    /// Returns the board mirrored left to right. Marks and revealed cells
+    /// move with it.
+    #[must_use]
+    pub fn flip_horizontal(&self) -> Self {
+        let width = self.size.0;
+        self.remap(self.size, |pos| CellPosition::new(width - 1 - pos.x, pos.y))
+    }
+
+    /// Returns the board mirrored top to bottom. Marks and revealed cells
+    /// move with it.
+    #[must_use]
+    pub fn flip_vertical(&self) -> Self {
+        let height = self.size.1;
+        self.remap(self.size, |pos| {
+            CellPosition::new(pos.x, height - 1 - pos.y)
+        })
+    }
+
+    /// Builds a board of `size` with every cell moved to `map(position)`.
+    /// The numbers are computed again from the moved mines.
+    fn remap(&self, size: (usize, usize), map: impl Fn(CellPosition) -> CellPosition) -> Self {
+        let mut board = Self {
+            cells: vec![Cell::default(); size.0 * size.1],
+            size,
+            mine_positions: self.mine_positions.iter().map(|pos| map(*pos)).collect(),
+            revealed_count: self.revealed_count,
+            flagged_count: self.flagged_count,
+            seed: 0,
+        };
+
+        for (pos, cell) in self.iter_cells() {
+            let new_pos = map(pos);
+            let new_index = board.index(new_pos);
+            let new_cell = &mut board.cells[new_index];
+            new_cell.state = cell.state;
+            if cell.is_mine() {
+                new_cell.content = CellContent::Mine;
+            }
+        }
+        board.calculate_adjacent_mines();
+        board.debug_assert_layout(self.mine_positions.len());
+
+        board
+    }
+
     /// Validates the game difficulty settings.
     ///
     /// # Errors
-    /// * Returns `GameError::InvalidBoardSize` if board size is 0
+    /// * Returns `GameError::InvalidBoardSize` if a dimension is 0 or too large
+    ///   for GTK grid coordinates
     /// * Returns `GameError::InvalidMinesCount` if mines count is 0 or exceeds board capacity
     /// * Returns `GameError::InvalidCellPosition` if the position is invalid
     pub fn validate_difficulty(difficulty: GameDifficulty) -> GameResult<()> {
-        if difficulty.board_size == (0, 0) {
+        let (width, height) = difficulty.board_size;
+        if width == 0 || height == 0 {
             return Err(GameError::InvalidBoardSize(0));
         }
 
-        let board_capacity = difficulty.board_size.0 * difficulty.board_size.1;
+        // Cells are laid out on a GTK grid, which takes i32 coordinates
+        for dimension in [width, height] {
+            if i32::try_from(dimension).is_err() {
+                return Err(GameError::InvalidBoardSize(dimension));
+            }
+        }
+
+        let board_capacity = width.saturating_mul(height);
         if difficulty.mines_count >= board_capacity || difficulty.mines_count == 0 {
             return Err(GameError::InvalidMinesCount(
                 difficulty.mines_count,
@@ -80,6 +437,59 @@ impl Board {
         Ok(())
     }
 
+    /// Checks a board read back from a save file: the cells must match its
+    /// size, the numbers its mines, and the counters its cells. A board
+    /// without mines passes as the [`blank`](Self::blank) board of a game
+    /// not started yet.
+    ///
+    /// # Errors
+    /// * Returns `GameError::MalformedBoard` if the board is inconsistent
+    /// * Returns the same errors as [`Board::with_mines`] for invalid layouts
+    pub fn check_layout(&self) -> GameResult<()> {
+        let malformed = |reason: &str| Err(GameError::MalformedBoard(reason.to_string()));
+
+        if self.cells.len() != self.size.0 * self.size.1 {
+            return malformed("cells don't match the board size");
+        }
+
+        let expected = if self.mine_positions.is_empty() {
+            Self::blank(self.size)
+        } else {
+            Self::with_mines(self.size, self.mine_positions.clone())?
+        };
+        let contents_match = self
+            .cells
+            .iter()
+            .zip(&expected.cells)
+            .all(|(cell, expected_cell)| cell.content == expected_cell.content);
+        if !contents_match {
+            return malformed("numbers don't match the mines");
+        }
+
+        let cells = self.cells.iter();
+        let revealed_count = cells.clone().filter(|cell| cell.is_revealed()).count();
+        let flagged_count = cells.filter(|cell| cell.is_flagged()).count();
+        if revealed_count != self.revealed_count
+            || isize::try_from(flagged_count).ok() != Some(self.flagged_count)
+        {
+            return malformed("counters don't match the cells");
+        }
+
+        Ok(())
+    }
+
+    /// Returns where the cell at `pos` is in `cells`. The position must be
+    /// valid.
+    const fn index(&self, pos: CellPosition) -> usize {
+        pos.to_index(self.size)
+    }
+
+    /// Returns the cell at `pos` for changing it. The position must be valid.
+    fn cell_mut(&mut self, pos: CellPosition) -> &mut Cell {
+        let index = self.index(pos);
+        &mut self.cells[index]
+    }
+
     const fn validate_position(&self, pos: CellPosition) -> GameResult<()> {
         if pos.x >= self.size.0 || pos.y >= self.size.1 {
             return Err(GameError::InvalidCellPosition(pos.x, pos.y));
@@ -114,7 +524,7 @@ impl Board {
         //     return Ok(false);
         // }
 
-        let was_flagged = self.cells[pos.x][pos.y].flag();
+        let was_flagged = self.cell_mut(pos).flag();
 
         if was_flagged {
             self.flagged_count = self.flagged_count.saturating_add(1);
@@ -137,7 +547,7 @@ impl Board {
     pub fn unflag(&mut self, pos: CellPosition) -> GameResult<bool> {
         self.validate_position(pos)?;
 
-        let was_unflagged = self.cells[pos.x][pos.y].unflag();
+        let was_unflagged = self.cell_mut(pos).unflag();
 
         if was_unflagged {
             self.flagged_count = self.flagged_count.saturating_sub(1);
@@ -147,6 +557,32 @@ impl Board {
         }
     }
 
+    /// Attempts to put a question mark on the cell at the given position.
+    ///
+    /// # Returns
+    /// * `GameResult<bool>` - Ok(true) if cell was marked, Ok(false) if it isn't a plain hidden cell
+    ///
+    /// # Errors
+    /// Returns error if the position is invalid
+    pub fn question(&mut self, pos: CellPosition) -> GameResult<bool> {
+        self.validate_position(pos)?;
+
+        Ok(self.cell_mut(pos).question())
+    }
+
+    /// Attempts to remove the question mark from the cell at the given position.
+    ///
+    /// # Returns
+    /// * `GameResult<bool>` - Ok(true) if the mark was removed, Ok(false) if the cell wasn't questioned
+    ///
+    /// # Errors
+    /// Returns error if the position is invalid
+    pub fn unquestion(&mut self, pos: CellPosition) -> GameResult<bool> {
+        self.validate_position(pos)?;
+
+        Ok(self.cell_mut(pos).unquestion())
+    }
+
     /// Attempts to reveals the cell at the given position. If the cell is a mine, the game is over.
     /// If the cell is empty, it will reveal all adjacent empty cells.
     ///
@@ -156,83 +592,200 @@ impl Board {
     /// # Returns
     /// * `GameResult<RevealResult>` -
     ///   `Ok(RevealResult::GameOver)` if the cell is a mine
-    ///   `Ok(RevealResult::Continue)`e) if the game continues
-    ///   `Ok(RevealResult::CantReveal)` if the cell is already revealed or flagged
+    ///   `Ok(RevealResult::Continue(1))` if the game continues
+    ///   `Ok(RevealResult::CantReveal)` with the reason if the cell is already
+    ///   revealed or flagged
     ///
     /// # Errors
     /// Returns error if the position is invalid or already revealed
     pub fn reveal(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
         self.validate_position(pos)?;
 
-        if self.cells[pos.x][pos.y].is_revealed() || self.cells[pos.x][pos.y].is_flagged() {
-            return Ok(RevealResult::CantReveal);
+        if self.cells[self.index(pos)].is_revealed() {
+            return Ok(RevealResult::CantReveal(CantReveal::AlreadyRevealed));
+        }
+        if self.cells[self.index(pos)].is_flagged() {
+            return Ok(RevealResult::CantReveal(CantReveal::Flagged));
         }
 
-        self.cells[pos.x][pos.y].reveal();
+        self.cell_mut(pos).reveal();
 
-        if self.cells[pos.x][pos.y].is_mine() {
+        if self.cells[self.index(pos)].is_mine() {
             return Ok(RevealResult::GameOver(pos));
         }
 
         self.revealed_count = self.revealed_count.saturating_add(1);
 
-        Ok(RevealResult::Continue)
+        Ok(RevealResult::Continue(1))
     }
 
-    /// Place mines on the board. The first revealed cell will not have a mine.
-    fn place_mines(&mut self, mines_count: usize, revealed_cell: CellPosition) {
-        let mut mines_placed = 0;
+    /// Place mines on the board, none of them on the `excluded` cells, which
+    /// must leave room for all of them.
+    ///
+    /// For weighted distributions, each mine picks the best of a few random
+    /// free cells, scored by how many mines are already next to it.
+    fn place_mines(
+        &mut self,
+        mines_count: usize,
+        excluded: &HashSet<CellPosition>,
+        distribution: MineDistribution,
+    ) {
+        let mut rng = fastrand::Rng::with_seed(self.seed);
 
-        let mut rng = fastrand::Rng::new();
+        let candidates = match distribution {
+            MineDistribution::Uniform => 1,
+            MineDistribution::Clustered | MineDistribution::Spread => DISTRIBUTION_CANDIDATES,
+        };
 
-        while mines_placed < mines_count {
-            let mine_pos = CellPosition {
+        for _ in 0..mines_count {
+            let mine_pos = (0..candidates)
+                .map(|_| self.random_free_position(&mut rng, excluded))
+                .max_by_key(|pos| {
+                    let adjacent_mines = self.adjacent_mines(*pos);
+                    match distribution {
+                        MineDistribution::Uniform => 0,
+                        MineDistribution::Clustered => adjacent_mines,
+                        MineDistribution::Spread => 8 - adjacent_mines,
+                    }
+                })
+                .expect("There is always at least one candidate");
+
+            self.cell_mut(mine_pos).content = CellContent::Mine;
+            self.mine_positions.insert(mine_pos);
+        }
+    }
+
+    /// Picks a random cell that is neither a mine nor excluded.
+    fn random_free_position(
+        &self,
+        rng: &mut fastrand::Rng,
+        excluded: &HashSet<CellPosition>,
+    ) -> CellPosition {
+        loop {
+            let pos = CellPosition {
                 x: rng.usize(..self.size.0),
                 y: rng.usize(..self.size.1),
             };
 
-            if !self.cells[mine_pos.x][mine_pos.y].is_mine() && mine_pos != revealed_cell {
-                self.cells[mine_pos.x][mine_pos.y].content = CellContent::Mine;
-                self.mine_positions.insert(mine_pos);
-                mines_placed += 1;
+            if !self.cells[self.index(pos)].is_mine() && !excluded.contains(&pos) {
+                return pos;
             }
         }
     }
 
+    fn adjacent_mines(&self, pos: CellPosition) -> usize {
+        self.adjacent_positions(pos)
+            .filter(|adj_pos| self.cells[self.index(*adj_pos)].is_mine())
+            .count()
+    }
+
+    /// Moves the mine at `from` to a random hidden cell that is not a mine,
+    /// not `from` and not in `exclude`, keeping the mines count. Only the
+    /// numbers around the old and new positions are recalculated.
+    ///
+    /// # Returns
+    /// * `GameResult<Option<CellPosition>>` - The new mine position, or `None`
+    ///   if `from` isn't a mine or there is no free cell to move it to
+    ///
+    /// # Errors
+    /// Returns error if the position is invalid
+    pub fn relocate_mine(
+        &mut self,
+        from: CellPosition,
+        exclude: &HashSet<CellPosition>,
+    ) -> GameResult<Option<CellPosition>> {
+        self.validate_position(from)?;
+
+        if !self.cells[self.index(from)].is_mine() {
+            return Ok(None);
+        }
+
+        let candidates: Vec<CellPosition> = self
+            .iter_cells()
+            .filter(|(pos, cell)| {
+                *pos != from && !cell.is_mine() && cell.is_hidden() && !exclude.contains(pos)
+            })
+            .map(|(pos, _)| pos)
+            .collect();
+
+        let mut rng = fastrand::Rng::with_seed(self.seed);
+        let Some(&to) = rng.choice(candidates.iter()) else {
+            return Ok(None);
+        };
+
+        self.mine_positions.remove(&from);
+        self.mine_positions.insert(to);
+        self.cell_mut(to).content = CellContent::Mine;
+        self.cell_mut(from).content = CellContent::Empty;
+
+        let affected: Vec<CellPosition> = [from, to]
+            .into_iter()
+            .chain(self.adjacent_positions(from))
+            .chain(self.adjacent_positions(to))
+            .collect();
+        for pos in affected {
+            self.recalculate_number(pos);
+        }
+        self.debug_assert_layout(self.mine_positions.len());
+
+        Ok(Some(to))
+    }
+
+    fn recalculate_number(&mut self, pos: CellPosition) {
+        if self.cells[self.index(pos)].is_mine() {
+            return;
+        }
+
+        let adjacent_mines = self.adjacent_mines(pos);
+        let content = &mut self.cell_mut(pos).content;
+        *content = CellContent::Empty;
+        for _ in 0..adjacent_mines {
+            content.add_one();
+        }
+    }
+
     /// Reveals all mines on the board.
     /// Used when the game is over.
-    ///
-    /// # Panics
-    /// Will panic if `self.mine_positions` vector has invalid positions.
     pub fn reveal_mines(&mut self) {
-        let mine_positions = self.mine_positions().clone();
-
-        for mine_pos in mine_positions {
-            if self.cells[mine_pos.x][mine_pos.y].is_flagged() {
-                continue;
+        for (_, cell) in self.iter_cells_mut() {
+            if cell.is_mine() && !cell.is_flagged() {
+                cell.reveal();
             }
-            self.cells[mine_pos.x][mine_pos.y].reveal();
         }
     }
 
     /// Flags all mines on the board.
     /// Used when the game is over.
-    ///
-    /// # Panics
-    /// Will panic if `self.mine_positions` vector has invalid positions.
     pub fn flag_mines(&mut self) {
-        let mine_positions = self.mine_positions().clone();
-
-        for mine_pos in mine_positions {
-            if self.cells[mine_pos.x][mine_pos.y].is_flagged() {
-                continue;
+        let mut flagged = 0;
+        for (_, cell) in self.iter_cells_mut() {
+            if cell.is_mine() && !cell.is_flagged() {
+                cell.flag();
+                flagged += 1;
             }
-            self.cells[mine_pos.x][mine_pos.y].flag();
-            self.flagged_count = self.flagged_count.saturating_add(1);
         }
+        self.flagged_count = self.flagged_count.saturating_add(flagged);
+    }
+
+    /// Hides every cell again and takes all marks off, leaving the mines and
+    /// numbers where they are.
+    pub fn reset_states(&mut self) {
+        for cell in &mut self.cells {
+            cell.state = CellState::Hidden;
+        }
+        self.revealed_count = 0;
+        self.flagged_count = 0;
     }
 
     pub fn adjacent_positions(&self, pos: CellPosition) -> impl Iterator<Item = CellPosition> + '_ {
+        Self::positions_around(pos, self.size)
+    }
+
+    /// Returns the positions next to `pos` on a board of `size`.
+    fn positions_around(
+        pos: CellPosition,
+        size: (usize, usize),
+    ) -> impl Iterator<Item = CellPosition> {
         const OFFSETS: &[(isize, isize)] = &[
             (-1, -1),
             (-1, 0),
@@ -248,16 +801,63 @@ impl Board {
             let new_x = pos.x.checked_add_signed(*dx)?;
             let new_y = pos.y.checked_add_signed(*dy)?;
 
-            (new_x < self.size.0 && new_y < self.size.1)
-                .then_some(CellPosition { x: new_x, y: new_y })
+            (new_x < size.0 && new_y < size.1).then_some(CellPosition { x: new_x, y: new_y })
         })
     }
 
+    /// Checks that the mines and the numbers around them agree, in debug
+    /// builds only.
+    fn debug_assert_layout(&self, mines_count: usize) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        let mine_cells = self.cells.iter().filter(|cell| cell.is_mine());
+        debug_assert_eq!(
+            self.mine_positions.len(),
+            mines_count,
+            "Wrong number of mines placed"
+        );
+        debug_assert_eq!(
+            mine_cells.count(),
+            mines_count,
+            "Mine cells and mine positions disagree"
+        );
+        debug_assert!(
+            self.mine_positions
+                .iter()
+                .all(|pos| self.cells[self.index(*pos)].is_mine()),
+            "Mine position without a mine"
+        );
+
+        // Each number counts the mines next to it, so all numbers together
+        // count every pair of a mine and a safe neighbour.
+        let numbers_total: usize = self
+            .cells
+            .iter()
+            .filter(|cell| !cell.is_mine())
+            .map(|cell| usize::from(cell.content.as_number()))
+            .sum();
+        let mine_safe_pairs: usize = self
+            .mine_positions
+            .iter()
+            .map(|pos| {
+                self.adjacent_positions(*pos)
+                    .filter(|adj_pos| !self.cells[self.index(*adj_pos)].is_mine())
+                    .count()
+            })
+            .sum();
+        debug_assert_eq!(
+            numbers_total, mine_safe_pairs,
+            "Numbers don't match the mines around them"
+        );
+    }
+
     fn calculate_adjacent_mines(&mut self) {
+        let size = self.size;
         for mine in &self.mine_positions {
-            let adj_positions: Vec<CellPosition> = self.adjacent_positions(*mine).collect();
-            for adj_pos in adj_positions {
-                self.cells[adj_pos.x][adj_pos.y].content.add_one();
+            for adj_pos in Self::positions_around(*mine, size) {
+                self.cells[adj_pos.to_index(size)].content.add_one();
             }
         }
     }
@@ -275,7 +875,27 @@ impl Board {
     pub fn cell(&self, pos: CellPosition) -> GameResult<&Cell> {
         self.validate_position(pos)?;
 
-        Ok(&self.cells[pos.x][pos.y])
+        Ok(&self.cells[self.index(pos)])
+    }
+
+    /// Returns every cell with its position, column after column.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (CellPosition, &Cell)> + '_ {
+        let size = self.size;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| (CellPosition::from_index(index, size), cell))
+    }
+
+    /// Returns every cell with its position for changing it, column after
+    /// column. The counts and mine positions are left for the caller to
+    /// keep in step.
+    fn iter_cells_mut(&mut self) -> impl Iterator<Item = (CellPosition, &mut Cell)> + '_ {
+        let size = self.size;
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, cell)| (CellPosition::from_index(index, size), cell))
     }
 
     #[must_use]
@@ -288,13 +908,617 @@ impl Board {
         self.revealed_count
     }
 
+    /// Returns whether every cell without a mine has been revealed.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.remaining_safe_cells() == 0
+    }
+
+    /// Returns the number of cells without a mine still to reveal.
+    #[must_use]
+    pub fn remaining_safe_cells(&self) -> usize {
+        self.size.0 * self.size.1 - self.mine_positions.len() - self.revealed_count
+    }
+
     #[must_use]
     pub const fn flagged_count(&self) -> isize {
         self.flagged_count
     }
 
+    /// Returns the number of mines that are not flagged yet. Unlike the
+    /// flags counter, wrong flags don't lower it.
+    #[must_use]
+    pub fn remaining_mines(&self) -> usize {
+        self.mine_positions
+            .iter()
+            .filter(|pos| !self.cells[self.index(**pos)].is_flagged())
+            .count()
+    }
+
+    /// Returns the number of question marks. Unlike flags, they don't count
+    /// against the mines.
+    #[must_use]
+    pub fn questioned_count(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|cell| cell.is_questioned())
+            .count()
+    }
+
+    /// Returns the share of cells holding a mine, from 0 to 1.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn density(&self) -> f64 {
+        self.mine_positions.len() as f64 / (self.size.0 * self.size.1) as f64
+    }
+
     #[must_use]
     pub const fn size(&self) -> (usize, usize) {
         self.size
     }
+
+    /// Returns the seed the mine layout was generated from.
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a hash of the board size and mine positions, equal for boards
+    /// with the same layout. It is FNV-1a over the sorted positions, so it is
+    /// stable across runs and Rust versions, unlike the std hasher.
+    #[must_use]
+    pub fn layout_hash(&self) -> u64 {
+        let mut mines: Vec<CellPosition> = self.mine_positions.iter().copied().collect();
+        mines.sort_unstable();
+
+        let values = [self.size.0, self.size.1]
+            .into_iter()
+            .chain(mines.into_iter().flat_map(|pos| [pos.x, pos.y]));
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for value in values {
+            for byte in (value as u64).to_le_bytes() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    /// Calculates the board's 3BV: the minimum number of left clicks needed
+    /// to clear it. Every opening (a connected region of empty cells plus its
+    /// numbered border) counts as one, and so does every number not bordering
+    /// an opening.
+    #[must_use]
+    pub fn calculate_3bv(&self) -> usize {
+        let mut visited = vec![false; self.cells.len()];
+        let mut three_bv = 0;
+
+        for index in 0..self.cells.len() {
+            if visited[index] || !self.cells[index].is_empty() {
+                continue;
+            }
+
+            three_bv += 1;
+            visited[index] = true;
+            let mut to_visit = vec![CellPosition::from_index(index, self.size)];
+
+            while let Some(pos) = to_visit.pop() {
+                for adj_pos in self.adjacent_positions(pos) {
+                    let adj_index = self.index(adj_pos);
+                    if visited[adj_index] {
+                        continue;
+                    }
+                    visited[adj_index] = true;
+
+                    if self.cells[adj_index].is_empty() {
+                        to_visit.push(adj_pos);
+                    }
+                }
+            }
+        }
+
+        let isolated_numbers = self
+            .cells
+            .iter()
+            .zip(&visited)
+            .filter(|(cell, visited)| !**visited && !cell.is_mine())
+            .count();
+
+        three_bv + isolated_numbers
+    }
+}
+
+/// Splits a text grid into its cells, with each line a row of the board
+/// from top to bottom and each character a cell from left to right.
+/// Returns the board size and the symbol at each position. Trailing blank
+/// lines are ignored.
+///
+/// # Errors
+/// Returns `GameError::MalformedBoard` if rows have different lengths or
+/// contain characters `known` rejects
+fn parse_grid(
+    grid: &str,
+    known: impl Fn(char) -> bool,
+) -> GameResult<((usize, usize), GridSymbols)> {
+    let rows: Vec<&str> = grid.trim_end().lines().collect();
+
+    let height = rows.len();
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    let mut symbols = Vec::with_capacity(width * height);
+
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() != width {
+            return Err(GameError::MalformedBoard(format!(
+                "row {} has {} cells, expected {width}",
+                y + 1,
+                row.chars().count()
+            )));
+        }
+
+        for (x, symbol) in row.chars().enumerate() {
+            if !known(symbol) {
+                return Err(GameError::MalformedBoard(format!(
+                    "unknown cell '{symbol}' at row {}, column {}",
+                    y + 1,
+                    x + 1
+                )));
+            }
+            symbols.push((CellPosition { x, y }, symbol));
+        }
+    }
+
+    Ok(((width, height), symbols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_mines_skips_flagged_ones() {
+        let mut board = Board::new(
+            GameDifficulty::EXPERT,
+            CellPosition::new(15, 8),
+            None,
+            Some(1285),
+        )
+        .unwrap();
+        let flagged_mines: HashSet<_> = board.mine_positions().iter().copied().take(10).collect();
+        let wrong_flag = board
+            .iter_cells()
+            .find(|(_, cell)| !cell.is_mine())
+            .map(|(pos, _)| pos)
+            .unwrap();
+        for pos in flagged_mines.iter().chain([&wrong_flag]) {
+            assert!(board.flag(*pos).unwrap());
+        }
+
+        board.reveal_mines();
+        for (pos, cell) in board.iter_cells() {
+            if flagged_mines.contains(&pos) || pos == wrong_flag {
+                assert!(cell.is_flagged());
+            } else {
+                assert_eq!(cell.is_revealed(), cell.is_mine());
+            }
+        }
+        assert_eq!(board.flagged_count(), 11);
+        assert_eq!(board.revealed_count(), 0);
+    }
+
+    /// Returns a path in the temporary directory no other test uses.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rusty-minesweeper-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn grid_file_round_trip() {
+        let path = temp_path("grid-round-trip.txt");
+        let grid = "*..\n...\n..*\n.*.\n";
+        fs::write(&path, grid).unwrap();
+
+        let board = Board::from_grid_file(&path).unwrap();
+        assert_eq!(board.size(), (3, 4));
+        assert_eq!(board.mine_positions().len(), 3);
+        assert!(board.cell(CellPosition::new(1, 3)).unwrap().is_mine());
+        assert_eq!(
+            board
+                .cell(CellPosition::new(1, 2))
+                .unwrap()
+                .content
+                .as_number(),
+            2
+        );
+
+        board.to_grid_file(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), grid);
+
+        let random = Board::new(
+            GameDifficulty::EXPERT,
+            CellPosition::new(0, 0),
+            None,
+            Some(1),
+        )
+        .unwrap();
+        random.to_grid_file(&path).unwrap();
+        assert_eq!(
+            Board::from_grid_file(&path).unwrap().mine_positions(),
+            random.mine_positions()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn grid_file_errors() {
+        let path = temp_path("grid-errors.txt");
+        for grid in ["*..\n..\n", "*x.\n", "...\n"] {
+            fs::write(&path, grid).unwrap();
+            assert!(Board::from_grid_file(&path).is_err(), "{grid:?}");
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            Board::from_grid_file(&temp_path("missing.txt")),
+            Err(GameError::IoError(_))
+        ));
+    }
+
+    /// Checks that every number on `board` counts the mines around it.
+    fn assert_numbers_match_mines(board: &Board) {
+        let fresh = Board::with_mines(board.size(), board.mine_positions().clone()).unwrap();
+        for ((pos, cell), (_, fresh_cell)) in board.iter_cells().zip(fresh.iter_cells()) {
+            assert_eq!(cell.content, fresh_cell.content, "at {pos:?}");
+        }
+    }
+
+    #[test]
+    fn relocated_mines_keep_the_count_and_numbers() {
+        let excluded = HashSet::from([CellPosition::new(1, 1)]);
+        for seed in 0..200 {
+            let mut board = Board::new(
+                GameDifficulty::EXPERT,
+                CellPosition::new(0, 0),
+                None,
+                Some(seed),
+            )
+            .unwrap();
+            let from = *board.mine_positions().iter().next().unwrap();
+
+            let to = board.relocate_mine(from, &excluded).unwrap().unwrap();
+            assert_ne!(to, from);
+            assert!(!excluded.contains(&to));
+            assert!(!board.cell(from).unwrap().is_mine());
+            assert_eq!(board.mine_positions().len(), 99);
+            assert_numbers_match_mines(&board);
+        }
+
+        let mut board =
+            Board::with_mines((2, 1), HashSet::from([CellPosition::new(0, 0)])).unwrap();
+        assert_eq!(
+            board
+                .relocate_mine(CellPosition::new(1, 0), &excluded)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn generated_boards_agree_with_their_mines() {
+        let mut rng = fastrand::Rng::with_seed(1182);
+        for _ in 0..500 {
+            let (width, height) = (rng.usize(2..=30), rng.usize(1..=24));
+            let mines_count = rng.usize(1..width * height);
+            let difficulty = GameDifficulty::new(width, height, mines_count).unwrap();
+            let first = CellPosition::new(rng.usize(..width), rng.usize(..height));
+            let board = Board::new(difficulty, first, None, Some(rng.u64(..))).unwrap();
+
+            assert_eq!(board.mine_positions().len(), mines_count);
+            assert!(!board.mine_positions().contains(&first));
+            let mine_cells: HashSet<_> = board
+                .iter_cells()
+                .filter(|(_, cell)| cell.is_mine())
+                .map(|(pos, _)| pos)
+                .collect();
+            assert_eq!(&mine_cells, board.mine_positions());
+
+            // Every mine adds one to each safe neighbour, and nothing else does
+            let edges: usize = board
+                .mine_positions()
+                .iter()
+                .map(|pos| {
+                    board
+                        .adjacent_positions(*pos)
+                        .filter(|adjacent| !board.mine_positions().contains(adjacent))
+                        .count()
+                })
+                .sum();
+            let numbers: usize = board
+                .iter_cells()
+                .filter(|(_, cell)| !cell.is_mine())
+                .map(|(_, cell)| usize::from(cell.content.as_number()))
+                .sum();
+            assert_eq!(numbers, edges);
+            assert_numbers_match_mines(&board);
+        }
+    }
+
+    #[test]
+    fn large_boards_within_grid_coordinates() {
+        let difficulty = GameDifficulty::new(2000, 1000, 1000).unwrap();
+        let corner = CellPosition::new(1999, 999);
+        let mut board = Board::new(difficulty, corner, None, Some(1183)).unwrap();
+        assert_eq!(board.cells.len(), 2_000_000);
+        assert!(matches!(
+            board.reveal(corner).unwrap(),
+            RevealResult::Continue(_)
+        ));
+        assert!(board.cell(CellPosition::new(2000, 0)).is_err());
+
+        let too_wide = usize::try_from(i32::MAX).unwrap() + 1;
+        assert!(matches!(
+            GameDifficulty::new(too_wide, 1, 1),
+            Err(GameError::InvalidBoardSize(size)) if size == too_wide
+        ));
+        assert!(matches!(
+            GameDifficulty::new(1, too_wide, 1),
+            Err(GameError::InvalidBoardSize(size)) if size == too_wide
+        ));
+        assert!(matches!(
+            GameDifficulty::new(0, 9, 1),
+            Err(GameError::InvalidBoardSize(0))
+        ));
+    }
+
+    #[test]
+    fn remaining_mines_ignores_wrong_flags() {
+        let mines = [(0, 0), (2, 0), (4, 4)].map(|(x, y)| CellPosition::new(x, y));
+        let mut board = Board::with_mines((5, 5), HashSet::from(mines)).unwrap();
+        assert_eq!(board.remaining_mines(), 3);
+
+        board.flag(CellPosition::new(1, 1)).unwrap();
+        board.flag(CellPosition::new(3, 3)).unwrap();
+        assert_eq!(board.remaining_mines(), 3);
+        assert_eq!(board.flagged_count(), 2);
+
+        board.flag(mines[0]).unwrap();
+        board.flag(mines[2]).unwrap();
+        assert_eq!(board.remaining_mines(), 1);
+        assert_eq!(board.flagged_count(), 4);
+
+        board.flag_mines();
+        assert_eq!(board.remaining_mines(), 0);
+    }
+
+    /// Returns a seeded 7x4 board with a few cells revealed and marked.
+    fn played_board() -> Board {
+        let mut board = Board::new(
+            GameDifficulty::new(7, 4, 6).unwrap(),
+            CellPosition::new(0, 0),
+            None,
+            Some(1201),
+        )
+        .unwrap();
+        board.reveal(CellPosition::new(0, 0)).unwrap();
+        let mine = *board.mine_positions().iter().min().unwrap();
+        board.flag(mine).unwrap();
+        let hidden = board
+            .iter_cells()
+            .find(|(_, cell)| cell.is_hidden() && !cell.is_flagged())
+            .map(|(pos, _)| pos)
+            .unwrap();
+        board.question(hidden).unwrap();
+        board
+    }
+
+    fn assert_same_cells(board: &Board, other: &Board) {
+        assert_eq!(board.size(), other.size());
+        assert_eq!(board.mine_positions(), other.mine_positions());
+        assert_eq!(board.revealed_count(), other.revealed_count());
+        assert_eq!(board.flagged_count(), other.flagged_count());
+        for ((pos, cell), (_, other_cell)) in board.iter_cells().zip(other.iter_cells()) {
+            assert_eq!(cell.content, other_cell.content, "at {pos:?}");
+            assert_eq!(cell.mark(), other_cell.mark(), "at {pos:?}");
+            assert_eq!(cell.is_revealed(), other_cell.is_revealed(), "at {pos:?}");
+        }
+    }
+
+    #[test]
+    fn transforms_undo_themselves() {
+        let board = played_board();
+        assert_same_cells(&board.transpose().transpose(), &board);
+        assert_same_cells(&board.flip_horizontal().flip_horizontal(), &board);
+        assert_same_cells(&board.flip_vertical().flip_vertical(), &board);
+    }
+
+    #[test]
+    fn transforms_move_cells_and_recompute_numbers() {
+        let board = played_board();
+        let (width, height) = board.size();
+        let transforms: [(Board, fn(CellPosition, (usize, usize)) -> CellPosition); 3] = [
+            (board.transpose(), |pos, _| CellPosition::new(pos.y, pos.x)),
+            (board.flip_horizontal(), |pos, (width, _)| {
+                CellPosition::new(width - 1 - pos.x, pos.y)
+            }),
+            (board.flip_vertical(), |pos, (_, height)| {
+                CellPosition::new(pos.x, height - 1 - pos.y)
+            }),
+        ];
+
+        for (moved, map) in transforms {
+            assert_numbers_match_mines(&moved);
+            assert_eq!(moved.mine_positions().len(), 6);
+            for (pos, cell) in board.iter_cells() {
+                let moved_cell = moved.cell(map(pos, (width, height))).unwrap();
+                assert_eq!(cell.content, moved_cell.content, "at {pos:?}");
+                assert_eq!(cell.mark(), moved_cell.mark(), "at {pos:?}");
+                assert_eq!(cell.is_revealed(), moved_cell.is_revealed());
+            }
+        }
+        assert_eq!(board.transpose().size(), (height, width));
+    }
+
+    #[test]
+    fn layout_hash_identifies_the_mines() {
+        let seeded = |seed| {
+            Board::new(
+                GameDifficulty::EXPERT,
+                CellPosition::new(0, 0),
+                None,
+                Some(seed),
+            )
+            .unwrap()
+        };
+
+        let mut hashes = HashSet::new();
+        for seed in 0..200 {
+            let mut board = seeded(seed);
+            let hash = board.layout_hash();
+            assert_eq!(seeded(seed).layout_hash(), hash);
+            assert!(hashes.insert(hash), "seed {seed}");
+
+            // Playing the board doesn't change its layout
+            board.reveal(CellPosition::new(0, 0)).unwrap();
+            board.flag_mines();
+            assert_eq!(board.layout_hash(), hash);
+        }
+
+        // The same mines on a larger board are another layout
+        let mines = HashSet::from([CellPosition::new(0, 0)]);
+        assert_ne!(
+            Board::with_mines((3, 3), mines.clone())
+                .unwrap()
+                .layout_hash(),
+            Board::with_mines((3, 4), mines).unwrap().layout_hash()
+        );
+    }
+
+    #[test]
+    fn flat_cells_line_up_with_positions() {
+        let (width, height) = (7, 3);
+        let mines = [(0, 0), (6, 0), (3, 1), (6, 2)].map(|(x, y)| CellPosition::new(x, y));
+        let mut board = Board::with_mines((width, height), HashSet::from(mines)).unwrap();
+
+        let positions: Vec<CellPosition> = board.iter_cells().map(|(pos, _)| pos).collect();
+        assert_eq!(positions.len(), width * height);
+        for (index, pos) in positions.iter().enumerate() {
+            assert_eq!(board.index(*pos), index);
+            assert!(pos.x < width && pos.y < height);
+            assert_eq!(board.cell(*pos).unwrap().is_mine(), mines.contains(pos));
+        }
+        assert!(board.cell(CellPosition::new(width, 0)).is_err());
+        assert!(board.cell(CellPosition::new(0, height)).is_err());
+
+        // The corner numbers only see their own neighbours
+        assert_eq!(
+            board
+                .cell(CellPosition::new(5, 1))
+                .unwrap()
+                .content
+                .as_number(),
+            2
+        );
+        assert_eq!(
+            board
+                .cell(CellPosition::new(0, 2))
+                .unwrap()
+                .content
+                .as_number(),
+            0
+        );
+
+        let mut revealed = board.clone();
+        revealed.reveal_mines();
+        board.flag_mines();
+        for ((pos, cell), (_, revealed_cell)) in board.iter_cells().zip(revealed.iter_cells()) {
+            assert_eq!(cell.is_flagged(), mines.contains(&pos), "at {pos:?}");
+            assert_eq!(
+                revealed_cell.is_revealed(),
+                mines.contains(&pos),
+                "at {pos:?}"
+            );
+        }
+        assert_eq!(board.flagged_count(), 4);
+    }
+
+    #[test]
+    fn three_bv_of_known_layouts() {
+        let three_bv = |size, mines: &[(usize, usize)]| {
+            let mines = mines
+                .iter()
+                .map(|&(x, y)| CellPosition::new(x, y))
+                .collect();
+            Board::with_mines(size, mines).unwrap().calculate_3bv()
+        };
+
+        // One opening holds every safe cell
+        assert_eq!(three_bv((3, 3), &[(0, 0)]), 1);
+        // Eight numbers around the mine, none next to an opening
+        assert_eq!(three_bv((3, 3), &[(1, 1)]), 8);
+        assert_eq!(three_bv((3, 1), &[(1, 0)]), 2);
+        // An opening on each side of the mine, each taking its number
+        assert_eq!(three_bv((5, 1), &[(2, 0)]), 2);
+        // Four lone numbers left of the wall, one opening right of it
+        assert_eq!(three_bv((4, 4), &[(1, 0), (1, 1), (1, 2), (1, 3)]), 5);
+        // Two openings on a non-square board, split by a wall of mines
+        assert_eq!(three_bv((7, 2), &[(3, 0), (3, 1)]), 2);
+    }
+
+    #[test]
+    fn ascii_round_trip_keeps_wrong_flags_safe() {
+        let mut board = Board::new(
+            GameDifficulty::INTERMEDIATE,
+            CellPosition::new(8, 8),
+            None,
+            Some(1276),
+        )
+        .unwrap();
+        board.reveal(CellPosition::new(8, 8)).unwrap();
+        let mine = *board.mine_positions().iter().min().unwrap();
+        let safe = board
+            .iter_cells()
+            .find(|(_, cell)| cell.is_hidden() && !cell.is_mine())
+            .map(|(pos, _)| pos)
+            .unwrap();
+        board.flag(mine).unwrap();
+        board.flag(safe).unwrap();
+
+        let ascii = board.to_ascii();
+        assert_eq!(ascii.matches('F').count(), 1);
+        assert_eq!(ascii.matches('X').count(), 1);
+        assert_eq!(board.to_player_ascii().matches('F').count(), 2);
+
+        let read_back = Board::from_ascii(&ascii).unwrap();
+        assert_eq!(read_back.mine_positions(), board.mine_positions());
+        assert!(!read_back.cell(safe).unwrap().is_mine());
+        assert_same_cells(&read_back, &board);
+        assert_eq!(read_back.to_ascii(), ascii);
+    }
+
+    #[test]
+    fn protected_cells_never_get_mines() {
+        let mut rng = fastrand::Rng::with_seed(1282);
+        for _ in 0..300 {
+            let (width, height) = (rng.usize(2..=30), rng.usize(2..=16));
+            let mines_count = rng.usize(1..width * height / 2);
+            let difficulty = GameDifficulty::new(width, height, mines_count).unwrap();
+            let first = CellPosition::new(rng.usize(..width), rng.usize(..height));
+            let protected: HashSet<_> = (0..rng.usize(..width * height / 2))
+                .map(|_| CellPosition::new(rng.usize(..width), rng.usize(..height)))
+                .collect();
+            let board = Board::new(difficulty, first, Some(&protected), Some(rng.u64(..))).unwrap();
+
+            assert_eq!(board.mine_positions().len(), mines_count);
+            assert!(!board.mine_positions().contains(&first));
+            assert!(board.mine_positions().is_disjoint(&protected));
+            assert_numbers_match_mines(&board);
+        }
+
+        // Without room for the mines, only the revealed cell stays protected
+        let difficulty = GameDifficulty::new(3, 3, 8).unwrap();
+        let first = CellPosition::new(1, 1);
+        let protected = HashSet::from([CellPosition::new(0, 0), CellPosition::new(2, 2)]);
+        let board = Board::new(difficulty, first, Some(&protected), Some(1282)).unwrap();
+        assert_eq!(board.mine_positions().len(), 8);
+        assert!(!board.mine_positions().contains(&first));
+    }
 }
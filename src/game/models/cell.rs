@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CellContent {
     Mine,
     Empty,
@@ -72,7 +73,7 @@ impl Default for CellContent {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CellPosition {
     pub x: usize,
     pub y: usize,
@@ -83,14 +84,18 @@ impl CellPosition {
     pub const fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
+    /// Returns the position at `index` on a board of `board_size` (width,
+    /// height). The inverse of [`to_index`](Self::to_index).
     #[must_use]
     pub const fn from_index(index: usize, board_size: (usize, usize)) -> Self {
         Self {
-            x: index / board_size.0,
+            x: index / board_size.1,
             y: index % board_size.1,
         }
     }
 
+    /// Returns where the position is in a list of the cells of a board of
+    /// `board_size` (width, height), stored column after column.
     #[must_use]
     pub const fn to_index(self, board_size: (usize, usize)) -> usize {
         (self.x * board_size.1) + self.y
@@ -103,11 +108,12 @@ impl From<(usize, usize)> for CellPosition {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum CellState {
     Hidden,
     Revealed,
     Flagged,
+    Question,
 }
 
 impl Default for CellState {
@@ -116,17 +122,26 @@ impl Default for CellState {
     }
 }
 
-#[derive(Default, Clone, Copy)]
+/// A mark the player can put on a hidden cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mark {
+    None,
+    Flag,
+    Question,
+}
+
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
 pub struct Cell {
     pub content: CellContent,
     pub state: CellState,
-    pub board_size: usize, // lol, this is the only way I found to set the row/column size dynamically on position method of relm4
 }
 
 impl Cell {
+    /// Returns whether the cell is neither revealed nor flagged. Question marks
+    /// don't protect a cell, so questioned cells count as hidden.
     #[must_use]
     pub const fn is_hidden(self) -> bool {
-        matches!(self.state, CellState::Hidden)
+        matches!(self.state, CellState::Hidden | CellState::Question)
     }
     #[must_use]
     pub const fn is_revealed(self) -> bool {
@@ -137,6 +152,19 @@ impl Cell {
         matches!(self.state, CellState::Flagged)
     }
     #[must_use]
+    pub const fn is_questioned(self) -> bool {
+        matches!(self.state, CellState::Question)
+    }
+    /// Returns the mark on the cell. Revealed cells have none.
+    #[must_use]
+    pub const fn mark(self) -> Mark {
+        match self.state {
+            CellState::Hidden | CellState::Revealed => Mark::None,
+            CellState::Flagged => Mark::Flag,
+            CellState::Question => Mark::Question,
+        }
+    }
+    #[must_use]
     pub const fn is_mine(self) -> bool {
         matches!(self.content, CellContent::Mine)
     }
@@ -163,6 +191,22 @@ impl Cell {
             false
         }
     }
+    pub const fn question(&mut self) -> bool {
+        if matches!(self.state, CellState::Hidden) {
+            self.state = CellState::Question;
+            true
+        } else {
+            false
+        }
+    }
+    pub const fn unquestion(&mut self) -> bool {
+        if self.is_questioned() {
+            self.state = CellState::Hidden;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl fmt::Display for Cell {
@@ -171,6 +215,33 @@ impl fmt::Display for Cell {
             CellState::Hidden => write!(f, ""),
             CellState::Revealed => write!(f, "{}", self.content),
             CellState::Flagged => write!(f, "🚩"),
+            CellState::Question => write!(f, "❓"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_round_trips_on_non_square_boards() {
+        for board_size in [(30, 16), (16, 30), (1, 7), (7, 1), (9, 9)] {
+            let (width, height) = board_size;
+            let mut indexes = Vec::with_capacity(width * height);
+
+            for x in 0..width {
+                for y in 0..height {
+                    let pos = CellPosition::new(x, y);
+                    let index = pos.to_index(board_size);
+                    assert_eq!(CellPosition::from_index(index, board_size), pos);
+                    indexes.push(index);
+                }
+            }
+
+            // Every cell gets its own index, with none left over
+            indexes.sort_unstable();
+            assert!(indexes.into_iter().eq(0..width * height), "{board_size:?}");
         }
     }
 }
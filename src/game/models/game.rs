@@ -1,5 +1,9 @@
+use crate::error::GameResult;
+use crate::game::models::board::Board;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum GameStatus {
     Won,
     Lost,
@@ -50,29 +54,120 @@ impl Default for GameStatus {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How mines are spread over the board when it is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MineDistribution {
+    /// Every free cell is equally likely to get a mine, like the original game.
+    #[default]
+    Uniform,
+    /// Mines prefer cells next to already placed mines. Boards get big
+    /// openings and dense mine clumps, which usually makes them easier.
+    Clustered,
+    /// Mines avoid cells next to already placed mines. Boards get few
+    /// openings and many low numbers, which usually makes them harder.
+    Spread,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GameDifficulty {
     pub board_size: (usize, usize),
     pub mines_count: usize,
+    pub mine_distribution: MineDistribution,
+    /// Only play boards the solver clears from the first click without
+    /// guessing. Boards are generated until one is, which gets slower as the
+    /// board gets denser.
+    #[serde(default)]
+    pub no_guess: bool,
 }
 
 impl GameDifficulty {
+    // The standard sizes used by ranking sites. Anything else gets its own name.
     pub const BEGINNER: Self = Self {
         board_size: (9, 9),
         mines_count: 10,
+        mine_distribution: MineDistribution::Uniform,
+        no_guess: false,
     };
     pub const INTERMEDIATE: Self = Self {
         board_size: (16, 16),
         mines_count: 40,
+        mine_distribution: MineDistribution::Uniform,
+        no_guess: false,
     };
     pub const EXPERT: Self = Self {
         board_size: (30, 16),
-        mines_count: 100,
+        mines_count: 99,
+        mine_distribution: MineDistribution::Uniform,
+        no_guess: false,
     };
+    /// Where a custom game starts from, the Intermediate board.
     pub const CUSTOM: Self = Self {
-        board_size: (100, 100),
-        mines_count: 10,
+        board_size: (16, 16),
+        mines_count: 40,
+        mine_distribution: MineDistribution::Uniform,
+        no_guess: false,
     };
+    /// Smallest and largest cell size, in pixels, picked by
+    /// [`auto_cell_size`](Self::auto_cell_size).
+    pub const MIN_CELL_SIZE: usize = 12;
+    pub const MAX_CELL_SIZE: usize = 24;
+
+    pub const MIN_LEVEL: u8 = 1;
+    pub const MAX_LEVEL: u8 = 10;
+
+    /// Creates a difficulty of `width` by `height` cells with `mines_count`
+    /// mines spread uniformly.
+    ///
+    /// # Errors
+    /// Will return `GameError` if no board can be built with the size or
+    /// mines count, see [`Board::validate_difficulty`].
+    pub fn new(width: usize, height: usize, mines_count: usize) -> GameResult<Self> {
+        let difficulty = Self {
+            board_size: (width, height),
+            mines_count,
+            mine_distribution: MineDistribution::Uniform,
+            no_guess: false,
+        };
+        Board::validate_difficulty(difficulty)?;
+
+        Ok(difficulty)
+    }
+
+    /// Returns the cell size, in pixels, that makes the whole board fit in
+    /// `max_window` (width, height), so small boards get big cells and large
+    /// boards small ones. Kept between `MIN_CELL_SIZE` and `MAX_CELL_SIZE`.
+    #[must_use]
+    pub fn auto_cell_size(&self, max_window: (usize, usize)) -> usize {
+        let (width, height) = self.board_size;
+        let fit = (max_window.0 / width.max(1)).min(max_window.1 / height.max(1));
+
+        fit.clamp(Self::MIN_CELL_SIZE, Self::MAX_CELL_SIZE)
+    }
+
+    /// Builds a difficulty from a single level, for players who would rather
+    /// not pick the board size and mines count themselves. Levels out of
+    /// range are clamped.
+    ///
+    /// The board grows evenly from 9x9 at level 1 to 30x16 at level 10, and
+    /// the mine density from 12.4% to 20.8%. Level 1 matches Beginner and
+    /// level 10 matches Expert.
+    #[must_use]
+    pub fn from_level(level: u8) -> Self {
+        let level = level.clamp(Self::MIN_LEVEL, Self::MAX_LEVEL);
+        let step = usize::from(level - Self::MIN_LEVEL);
+        let steps = usize::from(Self::MAX_LEVEL - Self::MIN_LEVEL);
+
+        let width = 9 + step * 21 / steps;
+        let height = 9 + step * 7 / steps;
+        let density_permille = 124 + step * 84 / steps;
+
+        Self {
+            board_size: (width, height),
+            mines_count: width * height * density_permille / 1000,
+            mine_distribution: MineDistribution::Uniform,
+            no_guess: false,
+        }
+    }
 }
 
 impl Default for GameDifficulty {
@@ -80,3 +175,59 @@ impl Default for GameDifficulty {
         Self::BEGINNER
     }
 }
+
+/// The difficulties a player can pick. Each one is turned into a validated
+/// [`GameDifficulty`], so the UI never hands out an invalid one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Beginner,
+    Intermediate,
+    Expert,
+    /// Width, height, mines count and whether boards must be solvable
+    /// without guessing.
+    Custom(usize, usize, usize, bool),
+    /// See [`GameDifficulty::from_level`].
+    Level(u8),
+}
+
+impl Preset {
+    /// Returns the difficulty of the preset.
+    ///
+    /// # Errors
+    /// Will return `GameError` if a custom board size or mines count is invalid.
+    pub fn difficulty(self) -> GameResult<GameDifficulty> {
+        let difficulty = match self {
+            Self::Beginner => GameDifficulty::BEGINNER,
+            Self::Intermediate => GameDifficulty::INTERMEDIATE,
+            Self::Expert => GameDifficulty::EXPERT,
+            Self::Custom(width, height, mines_count, no_guess) => GameDifficulty {
+                no_guess,
+                ..GameDifficulty::new(width, height, mines_count)?
+            },
+            Self::Level(level) => GameDifficulty::from_level(level),
+        };
+
+        Board::validate_difficulty(difficulty)?;
+        Ok(difficulty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_match_the_standard_boards() {
+        for (preset, board_size, mines_count) in [
+            (Preset::Beginner, (9, 9), 10),
+            (Preset::Intermediate, (16, 16), 40),
+            (Preset::Expert, (30, 16), 99),
+        ] {
+            let difficulty = preset.difficulty().unwrap();
+            assert_eq!(difficulty.board_size, board_size, "{preset:?}");
+            assert_eq!(difficulty.mines_count, mines_count, "{preset:?}");
+            assert_eq!(difficulty.mine_distribution, MineDistribution::Uniform);
+            assert!(!difficulty.no_guess);
+        }
+    }
+}
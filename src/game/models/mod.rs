@@ -1,3 +1,5 @@
 pub mod board;
 pub mod cell;
 pub mod game;
+pub mod options;
+pub mod summary;
@@ -0,0 +1,111 @@
+use crate::game::models::cell::Mark;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_GENERATION_RETRY_BUDGET: u32 = 1000;
+
+/// Gameplay options that change how the game behaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GameOptions {
+    /// Chord every revealed number a new flag satisfies. Off by default, since
+    /// a wrong flag makes it reveal a mine.
+    pub auto_chord_on_flag: bool,
+    /// Keep mines off the cells around the first click too, so it always
+    /// opens an area instead of showing a lone number.
+    #[serde(default)]
+    pub first_click_opening: bool,
+    /// Reveal every empty cell of the board right after the first click.
+    /// Games played with it aren't leaderboard eligible.
+    pub open_all_zeros: bool,
+    /// Start the timer at 1 second, as the original game does. Turn it off
+    /// to start at 0. Summaries record which mode a game was played with, so
+    /// times of both modes are never compared.
+    pub timer_head_start: bool,
+    /// Refuse chords that rely on a flag the revealed numbers don't prove,
+    /// as the hidden cells could then be mines. A game where it refused a
+    /// chord isn't leaderboard eligible.
+    pub guard_unsafe_chords: bool,
+    /// The marks right-click cycles through.
+    pub mark_cycle: MarkCycle,
+    /// How many layouts constrained generators may try before giving up.
+    /// Lower it to trade board quality for speed on slow machines.
+    pub generation_retry_budget: u32,
+    /// What to do when the retry budget runs out.
+    pub generation_fallback: GenerationFallback,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self {
+            auto_chord_on_flag: false,
+            first_click_opening: false,
+            open_all_zeros: false,
+            timer_head_start: true,
+            guard_unsafe_chords: false,
+            mark_cycle: MarkCycle::default(),
+            generation_retry_budget: DEFAULT_GENERATION_RETRY_BUDGET,
+            generation_fallback: GenerationFallback::default(),
+        }
+    }
+}
+
+/// What board generation does when no layout met its constraints in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GenerationFallback {
+    /// Play the last generated board anyway.
+    #[default]
+    UseLastBoard,
+    /// Fail with `GameError::GenerationFailed`.
+    Fail,
+}
+
+/// The marks right-click cycles through, like the original game's "Marks (?)"
+/// setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MarkCycle {
+    /// No mark and flag.
+    #[default]
+    Two,
+    /// No mark, flag and question mark.
+    Three,
+}
+
+impl MarkCycle {
+    const fn marks(self) -> &'static [Mark] {
+        match self {
+            Self::Two => &[Mark::None, Mark::Flag],
+            Self::Three => &[Mark::None, Mark::Flag, Mark::Question],
+        }
+    }
+
+    /// Returns the mark that follows `mark`. A mark outside the cycle counts
+    /// as no mark.
+    #[must_use]
+    pub fn next(self, mark: Mark) -> Mark {
+        let marks = self.marks();
+        let index = marks.iter().position(|m| *m == mark).unwrap_or(0);
+
+        marks[(index + 1) % marks.len()]
+    }
+
+    /// Returns whether the player can put `mark` on a cell.
+    #[must_use]
+    pub fn contains(self, mark: Mark) -> bool {
+        self.marks().contains(&mark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_three_cycle_has_question_marks() {
+        for mark in [Mark::None, Mark::Flag] {
+            assert!(MarkCycle::Two.contains(mark));
+            assert!(MarkCycle::Three.contains(mark));
+        }
+        assert!(!MarkCycle::Two.contains(Mark::Question));
+        assert!(MarkCycle::Three.contains(Mark::Question));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::error::GameResult;
+use crate::game::models::cell::CellPosition;
+use crate::game::models::game::GameDifficulty;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::time::Duration;
+
+/// Version of the JSON result format. Bumped when a field changes meaning or
+/// goes away, so tools reading results can tell the formats apart.
+pub const SUMMARY_SCHEMA_VERSION: u32 = 1;
+
+/// Everything worth knowing about a finished game, gathered in one place.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameSummary {
+    pub won: bool,
+    #[serde(rename = "elapsed_seconds", serialize_with = "serialize_seconds")]
+    pub elapsed: Duration,
+    /// Reveals, flags and chords that changed the board.
+    pub clicks: usize,
+    /// Minimum number of clicks needed to clear the board.
+    pub three_bv: u32,
+    /// 3BV divided by the clicks used, as a percentage.
+    pub efficiency: f64,
+    /// Flags placed on cells that are not mines.
+    pub wrong_flags: usize,
+    pub difficulty: GameDifficulty,
+    pub seed: u64,
+    /// Whether the timer started at 1 second instead of 0.
+    pub timer_head_start: bool,
+    /// Cells revealed by the first click, to average over the games played.
+    pub first_opening_size: usize,
+    /// Which records the game competes in. Records are kept per difficulty
+    /// and category, so styles of play are never mixed.
+    #[serde(rename = "mode")]
+    pub category: ScoreCategory,
+}
+
+impl GameSummary {
+    /// Returns the summary as a single line of JSON, for sharing results
+    /// with other players and tools.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the summary can't be serialized.
+    pub fn to_json(&self) -> GameResult<String> {
+        #[derive(Serialize)]
+        struct VersionedSummary<'a> {
+            schema_version: u32,
+            #[serde(flatten)]
+            summary: &'a GameSummary,
+        }
+
+        Ok(serde_json::to_string(&VersionedSummary {
+            schema_version: SUMMARY_SCHEMA_VERSION,
+            summary: self,
+        })?)
+    }
+}
+
+fn serialize_seconds<S: Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64())
+}
+
+/// The kind of play a record belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+pub enum ScoreCategory {
+    /// Anything goes. Records saved before categories existed belong here.
+    #[default]
+    Standard,
+    /// No flag was placed during the game.
+    NoFlag,
+    /// Played on a board solvable without guessing, see
+    /// [`GameDifficulty::no_guess`]. Takes precedence over `NoFlag`, since
+    /// those boards are easier whatever the style of play.
+    NoGuess,
+}
+
+impl fmt::Display for ScoreCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => write!(f, "Standard"),
+            Self::NoFlag => write!(f, "No Flags"),
+            Self::NoGuess => write!(f, "No Guessing"),
+        }
+    }
+}
+
+/// What a lost board shows: the mine that went off, the other mines, and
+/// which flags were right or wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LossView {
+    pub detonated: CellPosition,
+    /// Mines that were neither hit nor flagged.
+    pub other_mines: Vec<CellPosition>,
+    pub correct_flags: Vec<CellPosition>,
+    /// Flags placed on cells that are not mines.
+    pub wrong_flags: Vec<CellPosition>,
+}
@@ -0,0 +1,70 @@
+use crate::error::GameResult;
+use crate::game::models::board::Board;
+use crate::game::models::cell::{CellPosition, Mark};
+use crate::game::models::game::GameDifficulty;
+use crate::game::models::options::GameOptions;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// What the player did in a [`ReplayEvent`], named after the
+/// [`GameState`](crate::game::state::GameState) method that does it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayAction {
+    Reveal,
+    Chord,
+    SmartChord,
+    /// Puts the mark on the cell, [`Mark::None`] takes it off.
+    Mark(Mark),
+    Sweep,
+    RevealAllZeros,
+    Undo,
+}
+
+/// One action of a recorded game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    pub action: ReplayAction,
+    /// The cell acted on, `None` for actions on the whole board.
+    pub pos: Option<CellPosition>,
+    /// Game time the action was taken at, pauses left out. Actions before
+    /// the first reveal are at 0.
+    pub at: Duration,
+}
+
+/// A recorded game. Playing its events on a game restarted from its seed,
+/// difficulty and options gives back the same board and the same game.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub difficulty: GameDifficulty,
+    /// Seed of the board played, which generates it again from the same
+    /// first click.
+    pub seed: u64,
+    pub options: GameOptions,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    /// Reads the replay saved at `path`.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be read or its difficulty
+    /// is invalid.
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let replay: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Board::validate_difficulty(replay.difficulty)?;
+
+        Ok(replay)
+    }
+
+    /// Writes the replay to `path` as JSON.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be written.
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        fs::write(path, serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+}
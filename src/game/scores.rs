@@ -0,0 +1,381 @@
+use crate::error::GameResult;
+use crate::game::models::game::GameDifficulty;
+use crate::game::models::summary::ScoreCategory;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The fastest win, in seconds, of each standard difficulty and
+/// [`ScoreCategory`]. Only Beginner, Intermediate and Expert keep records,
+/// other boards aren't comparable.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BestTimes {
+    times: BTreeMap<String, u64>,
+}
+
+impl BestTimes {
+    /// Reads the best times saved at `path`. A missing or unreadable file
+    /// gives no best times, so a corrupt save never keeps the game from
+    /// starting.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        load_json(path)
+    }
+
+    /// Writes the best times to `path`, creating its directory if needed.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be written.
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        save_json(self, path)
+    }
+
+    /// Returns the best time of the difficulty in `category`, for games whose
+    /// timer started at 1 second or at 0 as `timer_head_start` says.
+    #[must_use]
+    pub fn best(
+        &self,
+        difficulty: &GameDifficulty,
+        category: ScoreCategory,
+        timer_head_start: bool,
+    ) -> Option<u64> {
+        self.times
+            .get(&Self::key(difficulty, category, timer_head_start)?)
+            .copied()
+    }
+
+    /// Keeps `seconds` if it beats the best time of the difficulty in
+    /// `category`.
+    ///
+    /// # Returns
+    /// Returns `true` if it is a new best time.
+    pub fn record(
+        &mut self,
+        difficulty: &GameDifficulty,
+        category: ScoreCategory,
+        timer_head_start: bool,
+        seconds: u64,
+    ) -> bool {
+        let Some(key) = Self::key(difficulty, category, timer_head_start) else {
+            return false;
+        };
+
+        match self.times.get(&key) {
+            Some(&best) if best <= seconds => false,
+            _ => {
+                self.times.insert(key, seconds);
+                true
+            }
+        }
+    }
+
+    // Times of each category and timer mode are kept apart, so they are
+    // never compared. Standard times have no category in their key, as
+    // before categories existed, so older files load as Standard. No-guess
+    // boards of a standard size are told apart by their category.
+    fn key(
+        difficulty: &GameDifficulty,
+        category: ScoreCategory,
+        timer_head_start: bool,
+    ) -> Option<String> {
+        let mut key = standard_name(difficulty)?.to_string();
+        match category {
+            ScoreCategory::Standard => (),
+            ScoreCategory::NoFlag => key.push_str("-no-flag"),
+            ScoreCategory::NoGuess => key.push_str("-no-guess"),
+        }
+        if !timer_head_start {
+            key.push_str("-from-zero");
+        }
+
+        Some(key)
+    }
+}
+
+/// Games played on each standard difficulty, no-guess boards of its size
+/// included, with every other board counted together as custom.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Statistics {
+    difficulties: BTreeMap<String, DifficultyStatistics>,
+}
+
+/// The games played on one difficulty. Only finished games count, a game
+/// left for a new one is neither won nor lost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifficultyStatistics {
+    pub games_played: u32,
+    pub games_won: u32,
+    /// Wins in a row since the last loss.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Seconds on the clock over every game played.
+    pub total_seconds: u64,
+    /// Cells revealed by the first click, over the `openings` games that had
+    /// one.
+    #[serde(default)]
+    pub total_opening_size: u64,
+    #[serde(default)]
+    pub openings: u32,
+}
+
+impl DifficultyStatistics {
+    /// Returns the share of games won, from 0 to 100.
+    #[must_use]
+    pub fn win_percentage(&self) -> f64 {
+        if self.games_played == 0 {
+            return 0.0;
+        }
+
+        f64::from(self.games_won) / f64::from(self.games_played) * 100.0
+    }
+
+    /// Returns the average number of cells the first click revealed, or
+    /// `None` before any game had a first click.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_opening_size(&self) -> Option<f64> {
+        (self.openings > 0).then(|| self.total_opening_size as f64 / f64::from(self.openings))
+    }
+}
+
+impl Statistics {
+    const CUSTOM: &'static str = "custom";
+
+    /// Reads the statistics saved at `path`. Like best times, a missing or
+    /// unreadable file starts them over.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        load_json(path)
+    }
+
+    /// Writes the statistics to `path`, creating its directory if needed.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be written.
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        save_json(self, path)
+    }
+
+    /// Counts a finished game of `difficulty` that took `seconds`, with the
+    /// cells its first click revealed if it had one.
+    pub fn record(
+        &mut self,
+        difficulty: &GameDifficulty,
+        won: bool,
+        seconds: u64,
+        first_opening_size: Option<usize>,
+    ) {
+        let name = standard_name(difficulty).unwrap_or(Self::CUSTOM);
+        let statistics = self.difficulties.entry(name.to_string()).or_default();
+
+        statistics.games_played = statistics.games_played.saturating_add(1);
+        statistics.total_seconds = statistics.total_seconds.saturating_add(seconds);
+        if let Some(size) = first_opening_size {
+            statistics.total_opening_size = statistics
+                .total_opening_size
+                .saturating_add(u64::try_from(size).unwrap_or(u64::MAX));
+            statistics.openings = statistics.openings.saturating_add(1);
+        }
+        if won {
+            statistics.games_won = statistics.games_won.saturating_add(1);
+            statistics.current_streak = statistics.current_streak.saturating_add(1);
+            statistics.longest_streak = statistics.longest_streak.max(statistics.current_streak);
+        } else {
+            statistics.current_streak = 0;
+        }
+    }
+
+    /// Returns the statistics of Beginner, Intermediate, Expert and custom
+    /// boards, in that order, with the name to show for each.
+    pub fn by_difficulty(&self) -> impl Iterator<Item = (&'static str, DifficultyStatistics)> + '_ {
+        [
+            ("Beginner", "beginner"),
+            ("Intermediate", "intermediate"),
+            ("Expert", "expert"),
+            ("Custom", Self::CUSTOM),
+        ]
+        .into_iter()
+        .map(|(label, name)| {
+            (
+                label,
+                self.difficulties.get(name).copied().unwrap_or_default(),
+            )
+        })
+    }
+}
+
+/// Returns the name records of `difficulty` are kept under, or `None` if it
+/// isn't a standard difficulty. A no-guess board of a standard size shares
+/// its name, so best times and statistics file it the same way.
+const fn standard_name(difficulty: &GameDifficulty) -> Option<&'static str> {
+    let board = GameDifficulty {
+        no_guess: false,
+        ..*difficulty
+    };
+    match board {
+        GameDifficulty::BEGINNER => Some("beginner"),
+        GameDifficulty::INTERMEDIATE => Some("intermediate"),
+        GameDifficulty::EXPERT => Some("expert"),
+        _ => None,
+    }
+}
+
+// A missing or unreadable file gives the default value, so a corrupt save
+// never keeps the game from starting.
+pub(crate) fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save_json<T: Serialize>(value: &T, path: &Path) -> GameResult<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statistics_of(statistics: &Statistics, label: &str) -> DifficultyStatistics {
+        statistics
+            .by_difficulty()
+            .find(|(name, _)| *name == label)
+            .map(|(_, statistics)| statistics)
+            .unwrap()
+    }
+
+    #[test]
+    fn statistics_average_the_openings() {
+        let mut statistics = Statistics::default();
+        assert_eq!(
+            statistics_of(&statistics, "Beginner").average_opening_size(),
+            None
+        );
+
+        statistics.record(&GameDifficulty::BEGINNER, true, 10, Some(20));
+        statistics.record(&GameDifficulty::BEGINNER, false, 3, Some(1));
+        // A game that ended before its first click has no opening
+        statistics.record(&GameDifficulty::BEGINNER, false, 0, None);
+
+        let beginner = statistics_of(&statistics, "Beginner");
+        assert_eq!(beginner.games_played, 3);
+        assert_eq!(beginner.total_opening_size, 21);
+        assert_eq!(beginner.openings, 2);
+        assert_eq!(beginner.average_opening_size(), Some(10.5));
+        assert_eq!(
+            statistics_of(&statistics, "Expert").average_opening_size(),
+            None
+        );
+    }
+
+    #[test]
+    fn statistics_saved_without_openings_still_load() {
+        let json = r#"{"expert": {"games_played": 4, "games_won": 1, "current_streak": 0,
+            "longest_streak": 1, "total_seconds": 400}}"#;
+        let statistics: Statistics = serde_json::from_str(json).unwrap();
+
+        let expert = statistics_of(&statistics, "Expert");
+        assert_eq!(expert.games_played, 4);
+        assert_eq!(expert.openings, 0);
+        assert_eq!(expert.average_opening_size(), None);
+    }
+
+    #[test]
+    fn best_times_are_kept_per_category() {
+        let mut best_times = BestTimes::default();
+        let expert = GameDifficulty::EXPERT;
+        assert!(best_times.record(&expert, ScoreCategory::Standard, true, 100));
+        assert!(best_times.record(&expert, ScoreCategory::NoFlag, true, 150));
+        assert!(!best_times.record(&expert, ScoreCategory::NoFlag, true, 160));
+        assert!(best_times.record(&expert, ScoreCategory::NoFlag, false, 160));
+
+        assert_eq!(
+            best_times.best(&expert, ScoreCategory::Standard, true),
+            Some(100)
+        );
+        assert_eq!(
+            best_times.best(&expert, ScoreCategory::NoFlag, true),
+            Some(150)
+        );
+        assert_eq!(
+            best_times.best(&expert, ScoreCategory::NoFlag, false),
+            Some(160)
+        );
+        assert_eq!(
+            best_times.best(&expert, ScoreCategory::Standard, false),
+            None
+        );
+    }
+
+    #[test]
+    fn best_times_saved_without_categories_load_as_standard() {
+        let json = r#"{"beginner": 12, "expert-from-zero": 95}"#;
+        let best_times: BestTimes = serde_json::from_str(json).unwrap();
+
+        let beginner = GameDifficulty::BEGINNER;
+        assert_eq!(
+            best_times.best(&beginner, ScoreCategory::Standard, true),
+            Some(12)
+        );
+        assert_eq!(
+            best_times.best(&beginner, ScoreCategory::NoFlag, true),
+            None
+        );
+        assert_eq!(
+            best_times.best(&GameDifficulty::EXPERT, ScoreCategory::Standard, false),
+            Some(95)
+        );
+    }
+
+    #[test]
+    fn no_guess_boards_keep_their_own_records() {
+        let mut best_times = BestTimes::default();
+        let no_guess_expert = GameDifficulty {
+            no_guess: true,
+            ..GameDifficulty::EXPERT
+        };
+        assert!(best_times.record(&no_guess_expert, ScoreCategory::NoGuess, true, 80));
+
+        assert_eq!(
+            best_times.best(&no_guess_expert, ScoreCategory::NoGuess, true),
+            Some(80)
+        );
+        assert_eq!(
+            best_times.best(&GameDifficulty::EXPERT, ScoreCategory::Standard, true),
+            None
+        );
+    }
+
+    #[test]
+    fn no_guess_games_count_with_their_board() {
+        let no_guess_expert = GameDifficulty {
+            no_guess: true,
+            ..GameDifficulty::EXPERT
+        };
+        let mut statistics = Statistics::default();
+        statistics.record(&no_guess_expert, true, 90, Some(40));
+        assert_eq!(statistics_of(&statistics, "Expert").games_won, 1);
+        assert_eq!(statistics_of(&statistics, "Custom").games_played, 0);
+
+        let mut best_times = BestTimes::default();
+        assert!(best_times.record(&no_guess_expert, ScoreCategory::NoGuess, true, 90));
+        assert_eq!(
+            best_times.best(&GameDifficulty::EXPERT, ScoreCategory::NoGuess, true),
+            Some(90)
+        );
+        assert_eq!(
+            best_times.best(&GameDifficulty::EXPERT, ScoreCategory::Standard, true),
+            None
+        );
+    }
+}
@@ -0,0 +1,735 @@
+use crate::error::GameResult;
+use crate::game::models::board::{Board, RevealResult};
+use crate::game::models::cell::CellPosition;
+use crate::game::models::game::GameStatus;
+use crate::game::state::GameState;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// Cells tried while enumerating a group before it gets the density of the
+/// board instead, so a move never stalls on a tangled frontier.
+const ENUMERATION_BUDGET: usize = 200_000;
+
+/// A revealed number with the mines left around it and its hidden
+/// neighbors, see [`GameState::constraints`].
+type Constraint = (CellPosition, u8, Vec<CellPosition>);
+
+/// A single step the solver wants to play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Reveal(CellPosition),
+    Flag(CellPosition),
+}
+
+/// Classic number patterns along a line of hidden cells. The numbers are
+/// counted without the mines already flagged around them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Two 1s where the hidden cells of one are all next to the other too,
+    /// so the other's extra hidden cells are safe.
+    OneOne,
+    /// The mines are under both 1s, the cell under the 2 is safe.
+    OneTwoOne,
+    /// The mines are under both 2s, the cells under the 1s are safe.
+    OneTwoTwoOne,
+}
+
+impl Pattern {
+    const ALL: [Self; 3] = [Self::OneTwoTwoOne, Self::OneTwoOne, Self::OneOne];
+
+    const fn numbers(self) -> &'static [u8] {
+        match self {
+            Self::OneOne => &[1, 1],
+            Self::OneTwoOne => &[1, 2, 1],
+            Self::OneTwoTwoOne => &[1, 2, 2, 1],
+        }
+    }
+}
+
+/// How hard a board is to solve, measured by playing it with the solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Complexity {
+    pub three_bv: usize,
+    /// Rounds of deductions needed, each playing every move the board
+    /// proves at that point.
+    pub depth: usize,
+    /// Times the solver was stuck and had to guess.
+    pub guesses: usize,
+}
+
+impl Complexity {
+    /// Rates the board by its guesses, as they matter far more than the depth.
+    #[must_use]
+    pub const fn rating(&self) -> Rating {
+        match self.guesses {
+            0 => Rating::Easy,
+            1..=3 => Rating::Medium,
+            4..=9 => Rating::Hard,
+            _ => Rating::Extreme,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Easy,
+    Medium,
+    Hard,
+    Extreme,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Easy => write!(f, "Easy"),
+            Self::Medium => write!(f, "Medium"),
+            Self::Hard => write!(f, "Hard"),
+            Self::Extreme => write!(f, "Extreme"),
+        }
+    }
+}
+
+/// How [`Solver::autosolve`] left the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoSolveOutcome {
+    /// Every safe cell is revealed.
+    Solved,
+    /// Nothing follows from the numbers anymore, the next move is a guess.
+    Stuck,
+    /// A reveal hit a mine, as a flag the solver trusted was wrong.
+    HitMine,
+}
+
+impl AutoSolveOutcome {
+    /// Returns the outcome of a game the solver can't play any further.
+    #[must_use]
+    pub const fn of(status: &GameStatus) -> Self {
+        match status {
+            GameStatus::Won => Self::Solved,
+            GameStatus::Lost => Self::HitMine,
+            GameStatus::InProgress | GameStatus::New => Self::Stuck,
+        }
+    }
+}
+
+impl fmt::Display for AutoSolveOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Solved => write!(f, "Solved"),
+            Self::Stuck => write!(f, "Stuck, the next move needs a guess"),
+            Self::HitMine => write!(f, "Hit a mine, a flag was wrong"),
+        }
+    }
+}
+
+/// Finds moves that follow from the revealed numbers, without guessing.
+/// The flags on the board are taken as mines.
+pub struct Solver;
+
+impl Solver {
+    /// Returns the next certain move, or `None` if the game is over or only
+    /// a guess is left. The first move of a new game reveals the center
+    /// cell, which is always safe.
+    #[must_use]
+    pub fn next_move(game_state: &GameState) -> Option<Move> {
+        let status = game_state.status();
+        if status.is_over() {
+            return None;
+        }
+        if status.is_new() {
+            let (width, height) = game_state.difficulty().board_size;
+            return Some(Move::Reveal(CellPosition::new(width / 2, height / 2)));
+        }
+
+        Self::deductions(&game_state.constraints())
+            .into_iter()
+            .next()
+    }
+
+    /// Plays every certain move on the game, flagging and revealing until it
+    /// is won or only guesses are left. Like [`next_move`](Self::next_move),
+    /// it trusts the flags on the board, so a wrong one can lead to a mine.
+    /// A new game is started from its center cell.
+    ///
+    /// # Errors
+    /// Will return `GameError` if a move can't be played.
+    pub fn autosolve(game_state: &mut GameState) -> GameResult<AutoSolveOutcome> {
+        while let Some(next_move) = Self::next_move(game_state) {
+            // A move the game refuses would come back forever
+            if let RevealResult::CantReveal(_) = game_state.play_solver_move(next_move)? {
+                break;
+            }
+        }
+
+        Ok(AutoSolveOutcome::of(game_state.status()))
+    }
+
+    /// Plays the whole board from `start` and measures how hard it was. When
+    /// stuck, the solver guesses right, opening the first safe cell next to
+    /// the revealed area.
+    #[must_use]
+    pub fn estimate_complexity(board: &Board, start: CellPosition) -> Complexity {
+        Self::play(board, start, true)
+    }
+
+    /// Returns whether the whole board can be cleared from `start` without
+    /// ever guessing.
+    #[must_use]
+    pub fn is_solvable(board: &Board, start: CellPosition) -> bool {
+        Self::play(board, start, false).guesses == 0
+    }
+
+    /// Plays the whole board from `start`. When stuck, it guesses right if
+    /// `guess` is set, or gives up after counting the guess.
+    fn play(board: &Board, start: CellPosition, guess: bool) -> Complexity {
+        let (width, height) = board.size();
+        let safe_cells = width * height - board.mine_positions().len();
+        let mut revealed: HashSet<CellPosition> = HashSet::with_capacity(safe_cells);
+        let mut mines: HashSet<CellPosition> = HashSet::new();
+        let mut complexity = Complexity {
+            three_bv: board.calculate_3bv(),
+            depth: 0,
+            guesses: 0,
+        };
+
+        Self::open(board, start, &mut revealed);
+        while revealed.len() < safe_cells {
+            let moves = Self::deductions(&Self::board_constraints(board, &revealed, &mines));
+            if moves.is_empty() {
+                let Some(lucky_guess) = Self::lucky_guess(board, &revealed) else {
+                    break;
+                };
+                complexity.guesses += 1;
+                if !guess {
+                    break;
+                }
+                Self::open(board, lucky_guess, &mut revealed);
+                continue;
+            }
+
+            complexity.depth += 1;
+            for next_move in moves {
+                match next_move {
+                    Move::Reveal(pos) => Self::open(board, pos, &mut revealed),
+                    Move::Flag(pos) => {
+                        mines.insert(pos);
+                    }
+                }
+            }
+        }
+
+        complexity
+    }
+
+    /// Reveals `start` on the simulated board, with the opening around it.
+    fn open(board: &Board, start: CellPosition, revealed: &mut HashSet<CellPosition>) {
+        let mut to_open = VecDeque::from([start]);
+
+        while let Some(pos) = to_open.pop_front() {
+            let Ok(cell) = board.cell(pos) else {
+                continue;
+            };
+            if cell.is_mine() || !revealed.insert(pos) {
+                continue;
+            }
+            if cell.is_empty() {
+                to_open.extend(board.adjacent_positions(pos));
+            }
+        }
+    }
+
+    /// Returns the first safe cell next to the revealed area, or any safe
+    /// cell left if none touches it.
+    fn lucky_guess(board: &Board, revealed: &HashSet<CellPosition>) -> Option<CellPosition> {
+        let safe_hidden: Vec<CellPosition> = board
+            .iter_cells()
+            .filter(|(pos, cell)| !revealed.contains(pos) && !cell.is_mine())
+            .map(|(pos, _)| pos)
+            .collect();
+
+        safe_hidden
+            .iter()
+            .copied()
+            .find(|pos| {
+                board
+                    .adjacent_positions(*pos)
+                    .any(|adj| revealed.contains(&adj))
+            })
+            .or_else(|| safe_hidden.first().copied())
+    }
+
+    /// Same as [`GameState::constraints`], for a simulated game where only
+    /// the proven mines are flagged.
+    fn board_constraints(
+        board: &Board,
+        revealed: &HashSet<CellPosition>,
+        mines: &HashSet<CellPosition>,
+    ) -> Vec<(CellPosition, u8, Vec<CellPosition>)> {
+        let mut constraints = Vec::new();
+
+        for &pos in revealed {
+            let Ok(cell) = board.cell(pos) else {
+                continue;
+            };
+            let mut mines_adjacent: u8 = 0;
+            let mut hidden: Vec<CellPosition> = Vec::with_capacity(8);
+
+            for adj_pos in board.adjacent_positions(pos) {
+                if mines.contains(&adj_pos) {
+                    mines_adjacent += 1;
+                } else if !revealed.contains(&adj_pos) {
+                    hidden.push(adj_pos);
+                }
+            }
+
+            if !hidden.is_empty() {
+                let remaining = cell.content.as_number().saturating_sub(mines_adjacent);
+                constraints.push((pos, remaining, hidden));
+            }
+        }
+
+        // Keeps the deductions, and so the guesses, the same between runs
+        constraints.sort_unstable_by_key(|(pos, _, _)| *pos);
+        constraints
+    }
+
+    /// Returns the hidden and flagged cells the revealed numbers prove to be
+    /// mines. Unlike [`next_move`](Self::next_move), the player's flags are
+    /// not trusted, so a wrong flag is never in the result.
+    #[must_use]
+    pub fn certain_mines(game_state: &GameState) -> HashSet<CellPosition> {
+        Self::settle(game_state.unflagged_constraints()).0
+    }
+
+    /// Plays the deductions of the constraints over and over, without
+    /// touching the board. Returns the cells proven to be mines, the ones
+    /// proven safe, and the constraints left on the other cells.
+    fn settle(
+        mut constraints: Vec<Constraint>,
+    ) -> (
+        HashSet<CellPosition>,
+        HashSet<CellPosition>,
+        Vec<Constraint>,
+    ) {
+        let mut mines: HashSet<CellPosition> = HashSet::new();
+        let mut safe: HashSet<CellPosition> = HashSet::new();
+
+        loop {
+            // Take the cells found so far out of every number
+            for (_, remaining, hidden) in &mut constraints {
+                let found_mines = hidden.iter().filter(|pos| mines.contains(pos)).count();
+                *remaining = remaining.saturating_sub(u8::try_from(found_mines).unwrap_or(u8::MAX));
+                hidden.retain(|pos| !mines.contains(pos) && !safe.contains(pos));
+            }
+            constraints.retain(|(_, _, hidden)| !hidden.is_empty());
+
+            let moves = Self::deductions(&constraints);
+            if moves.is_empty() {
+                return (mines, safe, constraints);
+            }
+            for next_move in moves {
+                match next_move {
+                    Move::Flag(pos) => mines.insert(pos),
+                    Move::Reveal(pos) => safe.insert(pos),
+                };
+            }
+        }
+    }
+
+    /// Returns the chance, from 0 to 1, of a mine under each of the
+    /// `hidden_cells`, given the `constraints` of the revealed numbers and the
+    /// `mines_left` to find.
+    ///
+    /// The cells the numbers prove get 0 or 1. The other cells next to
+    /// numbers get the share of the mine layouts the numbers allow that put a
+    /// mine under them, each layout counting the same. The rest share the
+    /// mines left evenly, as do frontier cells too tangled to enumerate, so
+    /// the result is approximate.
+    #[must_use]
+    pub fn mine_probabilities(
+        constraints: Vec<Constraint>,
+        hidden_cells: &[CellPosition],
+        mines_left: usize,
+    ) -> HashMap<CellPosition, f64> {
+        let (mines, safe, constraints) = Self::settle(constraints);
+        let mut probabilities: HashMap<CellPosition, f64> =
+            HashMap::with_capacity(hidden_cells.len());
+        probabilities.extend(mines.into_iter().map(|pos| (pos, 1.0)));
+        probabilities.extend(safe.into_iter().map(|pos| (pos, 0.0)));
+
+        let mut cell_constraints: HashMap<CellPosition, Vec<usize>> = HashMap::new();
+        for (index, (_, _, hidden)) in constraints.iter().enumerate() {
+            for pos in hidden {
+                cell_constraints.entry(*pos).or_default().push(index);
+            }
+        }
+
+        // Numbers sharing hidden cells are enumerated together, in the order
+        // they touch each other so impossible layouts are cut short
+        let mut grouped: HashSet<CellPosition> = HashSet::with_capacity(cell_constraints.len());
+        let mut frontier: Vec<CellPosition> = cell_constraints.keys().copied().collect();
+        frontier.sort_unstable();
+        for start in frontier {
+            if !grouped.insert(start) {
+                continue;
+            }
+
+            let mut group = vec![start];
+            let mut next = 0;
+            while let Some(&pos) = group.get(next) {
+                next += 1;
+                for &index in &cell_constraints[&pos] {
+                    for adj_pos in &constraints[index].2 {
+                        if grouped.insert(*adj_pos) {
+                            group.push(*adj_pos);
+                        }
+                    }
+                }
+            }
+
+            if let Some(group_probabilities) =
+                Self::enumerate_group(&constraints, &cell_constraints, &group)
+            {
+                probabilities.extend(group.into_iter().zip(group_probabilities));
+            }
+        }
+
+        let expected_mines: f64 = probabilities.values().sum();
+        let others = hidden_cells
+            .iter()
+            .filter(|pos| !probabilities.contains_key(pos))
+            .count();
+        #[allow(clippy::cast_precision_loss)]
+        let density = ((mines_left as f64 - expected_mines) / others.max(1) as f64).clamp(0.0, 1.0);
+        for pos in hidden_cells {
+            probabilities.entry(*pos).or_insert(density);
+        }
+
+        probabilities
+    }
+
+    /// Counts, for each cell of `group`, the share of the mine layouts the
+    /// constraints allow that put a mine under it. Returns `None` if the
+    /// group has too many layouts to enumerate, or none fits, as wrong flags
+    /// can make the numbers contradict each other.
+    fn enumerate_group(
+        constraints: &[Constraint],
+        cell_constraints: &HashMap<CellPosition, Vec<usize>>,
+        group: &[CellPosition],
+    ) -> Option<Vec<f64>> {
+        let mut enumeration = Enumeration {
+            remaining: constraints
+                .iter()
+                .map(|(_, remaining, _)| *remaining)
+                .collect(),
+            unassigned: constraints
+                .iter()
+                .map(|(_, _, hidden)| hidden.len())
+                .collect(),
+            cell_constraints: group
+                .iter()
+                .map(|pos| cell_constraints[pos].clone())
+                .collect(),
+            mines: vec![false; group.len()],
+            mine_counts: vec![0; group.len()],
+            layouts: 0,
+            steps: 0,
+        };
+        if !enumeration.search(0) || enumeration.layouts == 0 {
+            return None;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        Some(
+            enumeration
+                .mine_counts
+                .iter()
+                .map(|&count| count as f64 / enumeration.layouts as f64)
+                .collect(),
+        )
+    }
+
+    /// Returns every move the constraints prove, in the order found.
+    fn deductions(constraints: &[(CellPosition, u8, Vec<CellPosition>)]) -> Vec<Move> {
+        let mut moves: Vec<Move> = Vec::new();
+        let mut add_moves = |cells: &[CellPosition], mine: bool| {
+            for &pos in cells {
+                let next_move = if mine {
+                    Move::Flag(pos)
+                } else {
+                    Move::Reveal(pos)
+                };
+                if !moves.contains(&next_move) {
+                    moves.push(next_move);
+                }
+            }
+        };
+
+        // A number is satisfied, or all of its hidden neighbors are mines
+        for (_, remaining, hidden) in constraints {
+            if *remaining == 0 {
+                add_moves(hidden, false);
+            } else if usize::from(*remaining) == hidden.len() {
+                add_moves(hidden, true);
+            }
+        }
+
+        // The hidden neighbors of a number include all of another's, so the
+        // cells only the first one touches hold the difference of their mines
+        for (_, remaining, hidden) in constraints {
+            for (_, sub_remaining, sub_hidden) in constraints {
+                if sub_hidden.len() >= hidden.len()
+                    || !sub_hidden.iter().all(|pos| hidden.contains(pos))
+                {
+                    continue;
+                }
+
+                let Some(mines) = remaining.checked_sub(*sub_remaining) else {
+                    continue;
+                };
+                let rest: Vec<CellPosition> = hidden
+                    .iter()
+                    .copied()
+                    .filter(|pos| !sub_hidden.contains(pos))
+                    .collect();
+
+                if mines == 0 {
+                    add_moves(&rest, false);
+                } else if usize::from(mines) == rest.len() {
+                    add_moves(&rest, true);
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Finds the [`Pattern`]s on the board, each with its numbered cells in
+    /// order. Only numbers whose hidden neighbors all lie on one line next to
+    /// the pattern count, like numbers along the edge of an opening.
+    #[must_use]
+    pub fn patterns(game_state: &GameState) -> Vec<(Pattern, Vec<CellPosition>)> {
+        let constraints: HashMap<CellPosition, (u8, Vec<CellPosition>)> = game_state
+            .constraints()
+            .into_iter()
+            .map(|(pos, remaining, hidden)| (pos, (remaining, hidden)))
+            .collect();
+
+        let mut starts: Vec<CellPosition> = constraints.keys().copied().collect();
+        starts.sort_unstable();
+
+        let mut patterns = Vec::new();
+        for start in starts {
+            for horizontal in [true, false] {
+                let found = Pattern::ALL.into_iter().find_map(|pattern| {
+                    Self::match_pattern(&constraints, start, horizontal, pattern)
+                        .map(|cells| (pattern, cells))
+                });
+                patterns.extend(found);
+            }
+        }
+
+        patterns
+    }
+
+    fn match_pattern(
+        constraints: &HashMap<CellPosition, (u8, Vec<CellPosition>)>,
+        start: CellPosition,
+        horizontal: bool,
+        pattern: Pattern,
+    ) -> Option<Vec<CellPosition>> {
+        let cells: Vec<CellPosition> = (0..pattern.numbers().len())
+            .map(|step| {
+                if horizontal {
+                    CellPosition::new(start.x + step, start.y)
+                } else {
+                    CellPosition::new(start.x, start.y + step)
+                }
+            })
+            .collect();
+
+        let mut hidden_lines: HashSet<usize> = HashSet::new();
+        for (pos, number) in cells.iter().zip(pattern.numbers()) {
+            let (remaining, hidden) = constraints.get(pos)?;
+            if remaining != number {
+                return None;
+            }
+            hidden_lines.extend(hidden.iter().map(|hidden_pos| {
+                if horizontal {
+                    hidden_pos.y
+                } else {
+                    hidden_pos.x
+                }
+            }));
+        }
+
+        // All hidden cells must be on a single line next to the numbers
+        let numbers_line = if horizontal { start.y } else { start.x };
+        if hidden_lines.len() != 1 || hidden_lines.contains(&numbers_line) {
+            return None;
+        }
+
+        if pattern == Pattern::OneOne {
+            let first = &constraints.get(&cells[0])?.1;
+            let second = &constraints.get(&cells[1])?.1;
+            let covers = |outer: &Vec<CellPosition>, inner: &Vec<CellPosition>| {
+                inner.len() < outer.len() && inner.iter().all(|pos| outer.contains(pos))
+            };
+            if !covers(first, second) && !covers(second, first) {
+                return None;
+            }
+        }
+
+        Some(cells)
+    }
+}
+
+/// Walks every mine layout of a group of frontier cells that the numbers
+/// around them allow, see [`Solver::enumerate_group`].
+struct Enumeration {
+    /// Mines each number still needs among its unassigned cells.
+    remaining: Vec<u8>,
+    /// Cells of each number not assigned yet.
+    unassigned: Vec<usize>,
+    /// Numbers touching each cell of the group.
+    cell_constraints: Vec<Vec<usize>>,
+    mines: Vec<bool>,
+    /// Layouts found with a mine under each cell of the group.
+    mine_counts: Vec<u64>,
+    layouts: u64,
+    steps: usize,
+}
+
+impl Enumeration {
+    /// Assigns the cells from `cell` on, counting every layout that fits.
+    /// Returns `false` if the budget ran out before all were found.
+    fn search(&mut self, cell: usize) -> bool {
+        self.steps += 1;
+        if self.steps > ENUMERATION_BUDGET {
+            return false;
+        }
+
+        if cell == self.mines.len() {
+            self.layouts += 1;
+            for (count, _) in self
+                .mine_counts
+                .iter_mut()
+                .zip(&self.mines)
+                .filter(|(_, mine)| **mine)
+            {
+                *count += 1;
+            }
+            return true;
+        }
+
+        for mine in [false, true] {
+            // Each number needs a mine left for this one, and enough cells
+            // left for the rest of its mines
+            let fits = self.cell_constraints[cell].iter().all(|&index| {
+                let needed = usize::from(self.remaining[index]);
+                if mine {
+                    needed > 0 && self.unassigned[index] >= needed
+                } else {
+                    self.unassigned[index] > needed
+                }
+            });
+            if !fits {
+                continue;
+            }
+
+            self.assign(cell, mine, true);
+            let finished = self.search(cell + 1);
+            self.assign(cell, mine, false);
+            if !finished {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Puts `mine` under the cell, or takes it back, in the counts of the
+    /// numbers around it.
+    fn assign(&mut self, cell: usize, mine: bool, apply: bool) {
+        self.mines[cell] = mine && apply;
+        for &index in &self.cell_constraints[cell] {
+            if apply {
+                self.unassigned[index] -= 1;
+                self.remaining[index] -= u8::from(mine);
+            } else {
+                self.unassigned[index] += 1;
+                self.remaining[index] += u8::from(mine);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::models::game::GameDifficulty;
+
+    /// Returns a game on a `width`x3 board with `mines` on the top row, the
+    /// two lower rows revealed.
+    fn top_row_game(width: usize, mines: &[usize]) -> GameState {
+        let mines = mines.iter().map(|x| CellPosition::new(*x, 0)).collect();
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((width, 3), mines).unwrap())
+            .unwrap();
+        game_state.reveal_cell(CellPosition::new(0, 2)).unwrap();
+        game_state
+    }
+
+    fn row(xs: &[usize]) -> Vec<CellPosition> {
+        xs.iter().map(|x| CellPosition::new(*x, 1)).collect()
+    }
+
+    #[test]
+    fn finds_one_two_one() {
+        let game_state = top_row_game(3, &[0, 2]);
+        assert_eq!(
+            Solver::patterns(&game_state),
+            vec![(Pattern::OneTwoOne, row(&[0, 1, 2]))]
+        );
+    }
+
+    #[test]
+    fn finds_one_two_two_one() {
+        let game_state = top_row_game(4, &[1, 2]);
+        assert_eq!(
+            Solver::patterns(&game_state),
+            vec![(Pattern::OneTwoTwoOne, row(&[0, 1, 2, 3]))]
+        );
+    }
+
+    #[test]
+    fn finds_one_one_only_where_one_covers_the_other() {
+        // The middle 1s see three hidden cells each, neither covers the other
+        let game_state = top_row_game(4, &[0, 3]);
+        assert_eq!(
+            Solver::patterns(&game_state),
+            vec![
+                (Pattern::OneOne, row(&[0, 1])),
+                (Pattern::OneOne, row(&[2, 3])),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_numbers_with_hidden_cells_on_both_sides() {
+        // Hidden cells above and below the line of numbers
+        let mines = [(0, 0), (2, 0), (1, 2)]
+            .into_iter()
+            .map(|(x, y)| CellPosition::new(x, y))
+            .collect();
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((3, 3), mines).unwrap())
+            .unwrap();
+        for x in 0..3 {
+            game_state.reveal_cell(CellPosition::new(x, 1)).unwrap();
+        }
+        assert!(Solver::patterns(&game_state).is_empty());
+    }
+}
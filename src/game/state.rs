@@ -1,19 +1,107 @@
-use crate::error::GameResult;
-use crate::game::models::board::{Board, RevealResult};
-use crate::game::models::cell::CellPosition;
-use crate::game::models::game::{GameDifficulty, GameStatus};
-use std::collections::{HashSet, VecDeque};
+use crate::error::{GameError, GameResult};
+use crate::game::models::board::{Board, CantReveal, FlagResult, RevealResult};
+use crate::game::models::cell::{Cell, CellContent, CellPosition, Mark};
+use crate::game::models::game::{GameDifficulty, GameStatus, Preset};
+use crate::game::models::options::GameOptions;
+use crate::game::models::summary::{GameSummary, LossView, ScoreCategory};
+use crate::game::replay::{Replay, ReplayAction, ReplayEvent};
+use crate::game::scores::BestTimes;
+use crate::game::solver::{Complexity, Move, Pattern, Solver};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
+/// CSS class of the cells the player has revealed.
+pub const REVEALED_CELL_CLASS: &str = "revealed-cell";
+/// Share of the board, in percent, the first click must open for a lucky start.
+const LUCKY_OPENING_PERCENT: usize = 10;
+/// Most actions [`GameState::undo`] can take back, to bound memory on large boards.
+const UNDO_DEPTH: usize = 50;
+
+/// What [`GameState::save_to_path`] writes. The timer is kept as the time
+/// elapsed, since instants can't outlive the process.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    board: Board,
+    difficulty: GameDifficulty,
+    status: GameStatus,
+    elapsed: Duration,
+    timer_head_start: bool,
+    clicks: usize,
+    assisted: bool,
+    flags_placed: bool,
+    detonated: Option<CellPosition>,
+    start_cell: Option<CellPosition>,
+    first_opening_size: Option<usize>,
+}
+
+/// What the game looked like before a player action, to take it back.
+/// The timer isn't part of it, undoing doesn't give time back.
+struct GameSnapshot {
+    board: Board,
+    status: GameStatus,
+    finish_time: Option<Instant>,
+    clicks: usize,
+    assisted: bool,
+    detonated: Option<CellPosition>,
+    start_cell: Option<CellPosition>,
+    first_opening_size: Option<usize>,
+    flags_placed: bool,
+    last_reveal_content: Option<CellContent>,
+    best_time_recorded: Option<bool>,
+    generation: u64,
+}
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct GameState {
+    /// Blank until the first click places the mines, see [`Board::blank`].
     board: Board,
     difficulty: GameDifficulty,
+    options: GameOptions,
     status: GameStatus,
     start_time: Option<Instant>,
-    elapsed_seconds: u64,
+    finish_time: Option<Instant>,
+    paused_at: Option<Instant>,
+    timer_head_start: bool,
+    clicks: usize,
+    generation_attempts: u32,
+    assisted: bool,
+    auto_chord_result: Option<RevealResult>,
     revealed_cells: HashSet<CellPosition>,
     flagged_cells: HashSet<CellPosition>,
-    custom_flags_remaining: isize,
+    last_reveal_content: Option<CellContent>,
+    detonated: Option<CellPosition>,
+    start_cell: Option<CellPosition>,
+    generation: u64,
+    first_opening_size: Option<usize>,
+    flags_placed: bool,
+    best_times: BestTimes,
+    best_time_recorded: Option<bool>,
+    new_best_time: bool,
+    /// Whether the end of the game was reported for the statistics. Undo
+    /// leaves it set, so a game only counts once.
+    finish_reported: bool,
+    undo_stack: VecDeque<GameSnapshot>,
+    /// Seed the next board is generated from, random if `None`.
+    requested_seed: Option<u64>,
+    hints_used: usize,
+    /// Whether the game replays the board of the previous one, or a board
+    /// loaded from text. Its board is kept instead of generated on the first
+    /// click, and as the mines are known it can't set records.
+    retried: bool,
+    /// Whether the actions are recorded for a replay. Retried and loaded
+    /// games aren't, their boards can't be generated again from the seed.
+    recording: bool,
+    replay_events: Vec<ReplayEvent>,
+    /// Whether the game plays a replay back, so it can't set records.
+    replaying: bool,
+    // Buffers of reveal_area, kept between reveals so big openings don't
+    // allocate on every click.
+    reveal_queue: VecDeque<CellPosition>,
+    reveal_visited: Vec<bool>,
 }
 
 impl GameState {
@@ -23,62 +111,421 @@ impl GameState {
     /// Will return `GameError` if the board size is 0 or the mines count is invalid.
     pub fn new(difficulty: GameDifficulty) -> GameResult<Self> {
         Board::validate_difficulty(difficulty)?;
-        // It generates a new board on start_game method, this board is wasted. TODO!
-        let board: Board = Board::new(difficulty, CellPosition::new(0, 0), None)?;
         Ok(Self {
-            board,
+            board: Board::blank(difficulty.board_size),
             difficulty,
+            options: GameOptions::default(),
             status: GameStatus::New,
             start_time: None,
+            finish_time: None,
+            paused_at: None,
+            timer_head_start: false,
+            clicks: 0,
+            generation_attempts: 0,
+            assisted: false,
+            auto_chord_result: None,
             revealed_cells: HashSet::with_capacity(
                 difficulty.board_size.0 * difficulty.board_size.1,
             ),
             flagged_cells: HashSet::with_capacity(difficulty.mines_count),
-            custom_flags_remaining: 0,
+            last_reveal_content: None,
+            detonated: None,
+            start_cell: None,
+            generation: 0,
+            first_opening_size: None,
+            flags_placed: false,
+            best_times: BestTimes::default(),
+            best_time_recorded: None,
+            new_best_time: false,
+            finish_reported: false,
+            undo_stack: VecDeque::with_capacity(UNDO_DEPTH),
+            requested_seed: None,
+            hints_used: 0,
+            retried: false,
+            recording: true,
+            replay_events: Vec::new(),
+            replaying: false,
+            reveal_queue: VecDeque::new(),
+            reveal_visited: Vec::new(),
         })
     }
 
-    /// Restarts the game with the same difficulty.
+    /// Restarts the game with the same difficulty. Only
+    /// [`change_difficulty`](Self::change_difficulty) changes it.
     ///
     /// # Errors
     /// Will return `GameError` if the board size is 0 or the mines count is invalid.
     pub fn restart(&mut self) -> GameResult<()> {
-        let board: Board = Board::new(self.difficulty, CellPosition::new(0, 0), None)?;
-        self.board = board;
+        self.restart_with_seed(None)
+    }
+
+    /// Restarts the game like [`restart`](Self::restart), with the board
+    /// generated from `seed` on the first click. Games with the same seed,
+    /// difficulty and first click have the same mines.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the board size is 0 or the mines count is invalid.
+    pub fn restart_with_seed(&mut self, seed: Option<u64>) -> GameResult<()> {
+        Board::validate_difficulty(self.difficulty)?;
+        self.board = Board::blank(self.difficulty.board_size);
         self.status = GameStatus::New;
         self.start_time = None;
-        self.elapsed_seconds = 0;
-        self.revealed_cells = HashSet::with_capacity(self.difficulty.board_size.pow(2));
+        self.finish_time = None;
+        self.paused_at = None;
+        self.timer_head_start = false;
+        self.clicks = 0;
+        self.generation_attempts = 0;
+        self.assisted = false;
+        self.auto_chord_result = None;
+        self.revealed_cells =
+            HashSet::with_capacity(self.difficulty.board_size.0 * self.difficulty.board_size.1);
         self.flagged_cells = HashSet::with_capacity(self.difficulty.mines_count);
-        self.custom_flags_remaining = 0;
+        self.last_reveal_content = None;
+        self.detonated = None;
+        self.start_cell = None;
+        self.first_opening_size = None;
+        self.flags_placed = false;
+        self.best_time_recorded = None;
+        self.new_best_time = false;
+        self.finish_reported = false;
+        self.undo_stack.clear();
+        self.requested_seed = seed;
+        self.hints_used = 0;
+        self.retried = false;
+        self.recording = true;
+        self.replay_events.clear();
+        self.replaying = false;
+        self.generation += 1;
+
+        Ok(())
+    }
+
+    /// Restarts the game on the board just played, with every cell hidden
+    /// again. The mines stay where they were, so the first click isn't
+    /// protected, and the game can't set records. Does nothing before the
+    /// first click, when there is no board yet.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the game cannot be restarted.
+    pub fn restart_same_board(&mut self) -> GameResult<()> {
+        if self.status.is_new() {
+            return Ok(());
+        }
+
+        let mut board = self.board.clone();
+        board.reset_states();
+        self.restart_with_seed(Some(board.seed()))?;
+        self.board = board;
+        self.retried = true;
+        self.recording = false;
+
+        Ok(())
+    }
+
+    /// Starts a game on `board`, such as one read with [`Board::from_ascii`].
+    /// The difficulty takes the size and mines count of the board. Cells
+    /// revealed or flagged on the board stay so, and if any is revealed the
+    /// game is already running. Like a retried game, the board is kept on
+    /// the first click and can't set records.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the board size or mines count is invalid.
+    /// The current game is kept then.
+    pub fn load_board(&mut self, board: Board) -> GameResult<()> {
+        let (width, height) = board.size();
+        let difficulty = GameDifficulty::new(width, height, board.mine_positions().len())?;
+        self.change_difficulty(difficulty)?;
+
+        self.flags_placed = board.flagged_count() > 0;
+        if board.revealed_count() > 0 {
+            self.start_time = Some(Instant::now());
+            self.status = GameStatus::InProgress;
+        }
+        self.board = board;
+        self.rebuild_cell_sets();
+        self.retried = true;
+        self.recording = false;
+        self.check_win_condition();
+        self.debug_assert_invariants();
+
+        Ok(())
+    }
+
+    /// Restarts the game with the difficulty and options of `replay`, on the
+    /// board it was played on, ready for its events. The game can't set
+    /// records.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the difficulty of the replay is invalid.
+    /// The current game is kept then.
+    pub fn start_replay(&mut self, replay: &Replay) -> GameResult<()> {
+        Board::validate_difficulty(replay.difficulty)?;
+        self.difficulty = replay.difficulty;
+        self.options = replay.options;
+        self.restart_with_seed(Some(replay.seed))?;
+        self.replaying = true;
 
         Ok(())
     }
 
+    /// Re-checks the win condition and wins the game if every safe cell is
+    /// revealed. Safe to call at any time: a won game stays won and a lost
+    /// game stays lost.
+    ///
+    /// # Returns
+    /// Returns `true` if the game is won.
+    pub fn evaluate_win(&mut self) -> bool {
+        if self.status.is_over() {
+            return self.status.is_won();
+        }
+
+        self.check_win_condition()
+    }
+
     fn check_win_condition(&mut self) -> bool {
-        if self.board.revealed_count()
-            == ((self.difficulty.board_size.0 * self.difficulty.board_size.1)
-                - self.difficulty.mines_count)
-            && !self.status.is_lost()
-        {
-            self.status = GameStatus::Won;
+        if self.board.is_complete() && !self.status.is_lost() {
+            self.finish_game(GameStatus::Won);
             self.board.flag_mines();
             self.flagged_cells.extend(self.board.mine_positions());
+            self.new_best_time = self.record_best_time();
             return true;
         }
         false
     }
 
-    // Starts the game with already 1 second elapsed as the original game does.
-    fn start_game(&mut self, revealed_cell: CellPosition) {
+    // Starts the game with already 1 second elapsed as the original game does,
+    // unless the head start is turned off in the options. No-guess difficulties
+    // retry boards until the solver clears one from the first click.
+    fn start_game(&mut self, revealed_cell: CellPosition) -> GameResult<()> {
+        if !self.retried {
+            // Marks put before the first click carry over to the mined board
+            let marks: Vec<(CellPosition, Mark)> = self
+                .board
+                .iter_cells()
+                .map(|(pos, cell)| (pos, cell.mark()))
+                .filter(|(_, mark)| *mark != Mark::None)
+                .collect();
+            let no_guess = self.difficulty.no_guess;
+            let (board, attempts) = Board::generate(
+                self.difficulty,
+                revealed_cell,
+                self.requested_seed,
+                &self.options,
+                |board| !no_guess || Solver::is_solvable(board, revealed_cell),
+            )?;
+            self.board = board;
+            for (pos, mark) in marks {
+                match mark {
+                    Mark::None => (),
+                    Mark::Flag => {
+                        self.board.flag(pos)?;
+                    }
+                    Mark::Question => {
+                        self.board.question(pos)?;
+                    }
+                }
+            }
+            self.generation_attempts = attempts;
+        }
+
+        let head_start = if self.options.timer_head_start {
+            Duration::from_secs(1)
+        } else {
+            Duration::ZERO
+        };
         self.start_time = Some(
             Instant::now()
-                .checked_sub(Duration::from_secs(1))
+                .checked_sub(head_start)
                 .unwrap_or_else(Instant::now),
         );
-        self.board = Board::new(self.difficulty, revealed_cell, Some(&self.flagged_cells))
-            .expect("Failed to create board. Bad difficulty?");
+        self.timer_head_start = self.options.timer_head_start;
+        self.start_cell = Some(revealed_cell);
         self.status = GameStatus::InProgress;
+
+        Ok(())
+    }
+
+    /// Fills the revealed and flagged cells from a board put in place as a
+    /// whole, so the UI draws every cell it already has revealed or flagged.
+    fn rebuild_cell_sets(&mut self) {
+        self.revealed_cells.clear();
+        self.flagged_cells.clear();
+        for (pos, cell) in self.board.iter_cells() {
+            if cell.is_revealed() {
+                self.revealed_cells.insert(pos);
+            } else if cell.is_flagged() {
+                self.flagged_cells.insert(pos);
+            }
+        }
+    }
+
+    /// Checks the invariants every player action must keep, in debug builds only.
+    fn debug_assert_invariants(&self) {
+        let (width, height) = self.board.size();
+        let safe_cells = width * height - self.difficulty.mines_count;
+
+        debug_assert!(
+            self.board.revealed_count() <= safe_cells,
+            "Revealed more cells than there are safe cells"
+        );
+        debug_assert!(
+            !self.status.is_won() || self.board.revealed_count() == safe_cells,
+            "Won with safe cells still hidden"
+        );
+        debug_assert!(
+            !self.status.is_over() || self.finish_time.is_some(),
+            "Game over without a finish time"
+        );
+        debug_assert!(
+            usize::try_from(self.board.flagged_count()).is_ok_and(|flags| flags <= width * height),
+            "Flag count out of range"
+        );
+    }
+
+    fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            board: self.board.clone(),
+            status: self.status,
+            finish_time: self.finish_time,
+            clicks: self.clicks,
+            assisted: self.assisted,
+            detonated: self.detonated,
+            start_cell: self.start_cell,
+            first_opening_size: self.first_opening_size,
+            flags_placed: self.flags_placed,
+            last_reveal_content: self.last_reveal_content,
+            best_time_recorded: self.best_time_recorded,
+            generation: self.generation,
+        }
+    }
+
+    /// Keeps `snapshot` for [`undo`](Self::undo) if the action taken since
+    /// changed the board. The oldest one is dropped past `UNDO_DEPTH`.
+    fn push_undo(&mut self, snapshot: GameSnapshot) {
+        if snapshot.generation == self.generation {
+            return;
+        }
+
+        if self.undo_stack.len() == UNDO_DEPTH {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(snapshot);
+    }
+
+    /// Records the action for the replay if it changed the board since
+    /// `generation`.
+    fn record(&mut self, action: ReplayAction, pos: Option<CellPosition>, generation: u64) {
+        if !self.recording || generation == self.generation {
+            return;
+        }
+
+        self.replay_events.push(ReplayEvent {
+            action,
+            pos,
+            at: self.elapsed(),
+        });
+    }
+
+    /// Returns the replay of the game so far, with the options it is played
+    /// with. Returns `None` before the first reveal, and for retried and
+    /// loaded games, whose actions aren't recorded.
+    #[must_use]
+    pub fn replay(&self) -> Option<Replay> {
+        if self.status.is_new() || !self.recording {
+            return None;
+        }
+
+        Some(Replay {
+            difficulty: self.difficulty,
+            seed: self.board.seed(),
+            options: self.options,
+            events: self.replay_events.clone(),
+        })
+    }
+
+    /// Returns whether there is an action [`undo`](Self::undo) can take back.
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Takes back the last reveal, mark or chord. A lost game is played on,
+    /// with the mines hidden again, and undoing the first reveal gives a new
+    /// game. The timer keeps running. Undoing makes the game leaderboard
+    /// ineligible.
+    ///
+    /// # Returns
+    /// Returns the cells to redraw, or `None` if there was nothing to undo.
+    pub fn undo(&mut self) -> Option<HashSet<CellPosition>> {
+        let snapshot = self.undo_stack.pop_back()?;
+        let generation = self.generation;
+
+        // The loss overlay goes away with the loss
+        let mut changed_cells: HashSet<CellPosition> = self
+            .loss_overlay()
+            .map(|loss_view| {
+                std::iter::once(loss_view.detonated)
+                    .chain(loss_view.wrong_flags)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (pos, cell) in self.board.iter_cells() {
+            let Ok(old_cell) = snapshot.board.cell(pos) else {
+                continue;
+            };
+            if old_cell.is_revealed() != cell.is_revealed()
+                || old_cell.to_string() != cell.to_string()
+            {
+                changed_cells.insert(pos);
+            }
+        }
+
+        if snapshot.status.is_new() {
+            self.start_time = None;
+            self.timer_head_start = false;
+        }
+        self.board = snapshot.board;
+        self.status = snapshot.status;
+        self.finish_time = snapshot.finish_time;
+        self.clicks = snapshot.clicks;
+        self.detonated = snapshot.detonated;
+        self.start_cell = snapshot.start_cell;
+        self.first_opening_size = snapshot.first_opening_size;
+        self.flags_placed = snapshot.flags_placed;
+        self.last_reveal_content = snapshot.last_reveal_content;
+        self.best_time_recorded = snapshot.best_time_recorded;
+        self.assisted = snapshot.assisted || !snapshot.status.is_new();
+        self.revealed_cells.clear();
+        // Flags placed before the first reveal are carried onto the new board
+        self.flagged_cells = self
+            .board
+            .iter_cells()
+            .filter(|(_, cell)| cell.is_flagged())
+            .map(|(pos, _)| pos)
+            .collect();
+        self.auto_chord_result = None;
+        self.generation += 1;
+        if snapshot.status.is_new() {
+            // The game is back before its first reveal, as if it was never played
+            let first_reveal = self
+                .replay_events
+                .iter()
+                .position(|event| event.action == ReplayAction::Reveal);
+            if let Some(first_reveal) = first_reveal {
+                self.replay_events.truncate(first_reveal);
+            }
+        } else {
+            self.record(ReplayAction::Undo, None, generation);
+        }
+        self.debug_assert_invariants();
+
+        Some(changed_cells)
+    }
+
+    fn finish_game(&mut self, status: GameStatus) {
+        self.resume();
+        self.status = status;
+        self.finish_time = Some(Instant::now());
     }
 
     /// Reveals the cell at the given position.
@@ -87,242 +534,2228 @@ impl GameState {
     /// * `pos` - The position of the cell to reveal
     ///
     /// # Returns
-    /// Returns a vector of revealed positions.
+    /// Returns the reveal result. `Continue` holds how many cells the click
+    /// revealed, including the cascade of an opening. Once the game is over
+    /// nothing is revealed and `CantReveal(CantReveal::NothingToReveal)` is
+    /// returned.
     ///
     /// # Errors
     /// Will return `GameError` if the position is invalid.
     pub fn reveal_cell(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
-        if self.board.cell(pos)?.is_revealed() || self.board.cell(pos)?.is_flagged() {
-            return Ok(RevealResult::CantReveal);
+        let snapshot = self.snapshot();
+        let reveal_result = self.reveal(pos)?;
+        if !matches!(reveal_result, RevealResult::CantReveal(_)) {
+            self.clicks += 1;
+            self.generation += 1;
         }
+        self.record(ReplayAction::Reveal, Some(pos), snapshot.generation);
+        self.push_undo(snapshot);
+        self.debug_assert_invariants();
 
-        if self.status.is_new() {
-            self.start_game(pos);
+        Ok(reveal_result)
+    }
+
+    fn reveal(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
+        if self.status.is_over() {
+            return Ok(RevealResult::CantReveal(CantReveal::NothingToReveal));
+        }
+        if self.board.cell(pos)?.is_revealed() {
+            return Ok(RevealResult::CantReveal(CantReveal::AlreadyRevealed));
+        }
+        if self.board.cell(pos)?.is_flagged() {
+            return Ok(RevealResult::CantReveal(CantReveal::Flagged));
+        }
+
+        let starting = self.status.is_new();
+        if starting {
+            self.start_game(pos)?;
         }
 
         let reveal_result = self.reveal_area(pos)?;
+        self.last_reveal_content = Some(self.board.cell(pos)?.content);
 
         match reveal_result {
-            RevealResult::Continue => {
-                if !self.status.is_lost() {
-                    self.check_win_condition();
+            RevealResult::Continue(mut count) => {
+                if starting {
+                    self.first_opening_size = Some(count);
+                }
+                if starting && self.options.open_all_zeros {
+                    count += self.open_all_zeros()?;
                 }
-                Ok(RevealResult::Continue)
+                self.evaluate_win();
+                Ok(RevealResult::Continue(count))
             }
             RevealResult::GameOver(mine_pos) => Ok(RevealResult::GameOver(mine_pos)),
-            RevealResult::CantReveal => Ok(RevealResult::CantReveal),
+            RevealResult::CantReveal(reason) => Ok(RevealResult::CantReveal(reason)),
+        }
+    }
+
+    /// Reveals every empty cell that isn't flagged, with the numbers around
+    /// it. Only safe cells are revealed, but the game becomes leaderboard
+    /// ineligible.
+    ///
+    /// # Returns
+    /// Returns the number of cells newly revealed.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the board is inconsistent.
+    pub fn reveal_all_zeros(&mut self) -> GameResult<usize> {
+        let snapshot = self.snapshot();
+        let revealed_count = self.open_all_zeros()?;
+        self.record(ReplayAction::RevealAllZeros, None, snapshot.generation);
+        self.push_undo(snapshot);
+
+        Ok(revealed_count)
+    }
+
+    fn open_all_zeros(&mut self) -> GameResult<usize> {
+        if !self.status.is_in_progress() {
+            return Ok(0);
         }
+
+        let empty_cells: Vec<CellPosition> = self
+            .board
+            .iter_cells()
+            .filter(|(_, cell)| cell.is_empty())
+            .map(|(pos, _)| pos)
+            .collect();
+        let mut revealed_count = 0;
+
+        for pos in empty_cells {
+            // An earlier opening may have revealed it already
+            if self.board.cell(pos)?.is_hidden() {
+                if let RevealResult::Continue(count) = self.reveal_area(pos)? {
+                    revealed_count += count;
+                }
+            }
+        }
+
+        if revealed_count > 0 {
+            self.assisted = true;
+            self.generation += 1;
+        }
+        self.evaluate_win();
+        self.debug_assert_invariants();
+
+        Ok(revealed_count)
     }
 
     fn reveal_area(&mut self, start_pos: CellPosition) -> GameResult<RevealResult> {
-        let mut to_reveal: VecDeque<CellPosition> = VecDeque::with_capacity(8);
-        let mut visited: HashSet<CellPosition> = HashSet::with_capacity(32);
+        let board_size = self.board.size();
+        self.reveal_queue.clear();
+        self.reveal_visited.clear();
+        self.reveal_visited
+            .resize(board_size.0 * board_size.1, false);
 
-        to_reveal.push_back(start_pos);
-        visited.insert(start_pos);
+        // Checks the position before it indexes the buffer
+        self.board.cell(start_pos)?;
+        self.reveal_queue.push_back(start_pos);
+        self.reveal_visited[start_pos.to_index(board_size)] = true;
+        let mut revealed_count = 0;
 
-        while let Some(pos) = to_reveal.pop_front() {
+        while let Some(pos) = self.reveal_queue.pop_front() {
             match self.board.reveal(pos)? {
-                RevealResult::Continue => {
+                RevealResult::Continue(count) => {
                     self.revealed_cells.insert(pos);
+                    revealed_count += count;
 
                     if self.board.cell(pos)?.is_empty() {
                         for adj_pos in self.board.adjacent_positions(pos) {
-                            if !visited.insert(adj_pos) {
+                            let visited = &mut self.reveal_visited[adj_pos.to_index(board_size)];
+                            if *visited {
                                 continue;
                             }
+                            *visited = true;
 
                             let cell = self.board.cell(adj_pos)?;
                             if !cell.is_revealed() && !cell.is_flagged() {
-                                to_reveal.push_back(adj_pos);
+                                self.reveal_queue.push_back(adj_pos);
                             }
                         }
                     }
                 }
                 RevealResult::GameOver(mine_pos) => {
                     self.revealed_cells.insert(pos);
-                    self.status = GameStatus::Lost;
+                    self.detonated = Some(mine_pos);
+                    self.finish_game(GameStatus::Lost);
                     self.board.reveal_mines();
 
                     self.revealed_cells.extend(self.board.mine_positions());
                     return Ok(RevealResult::GameOver(mine_pos));
                 }
-                RevealResult::CantReveal => return Ok(RevealResult::CantReveal),
+                RevealResult::CantReveal(reason) => return Ok(RevealResult::CantReveal(reason)),
             }
         }
 
-        Ok(RevealResult::Continue)
+        Ok(RevealResult::Continue(revealed_count))
     }
 
-    /// Toggles the flag of the cell at the given position.
+    /// Moves the mark of the cell at the given position one step along the
+    /// [`MarkCycle`](crate::game::models::options::MarkCycle) of the options,
+    /// as a right-click does: hidden, flagged, then question marked if the
+    /// cycle has three marks.
+    ///
+    /// Flagging is allowed before the first reveal and doesn't start the game.
     ///
     /// # Arguments
     /// * `pos` - The position of the cell to toggle the flag
     ///
     /// # Returns
-    /// Returns `true` if the flag was toggled, `false` if the cell is already revealed.
+    /// Returns the mark the cell got, or why the mark was refused.
     ///
     /// # Errors
     /// Will return `GameError` if position is invalid
-    pub fn toggle_flag(&mut self, pos: CellPosition) -> GameResult<bool> {
+    pub fn toggle_flag(&mut self, pos: CellPosition) -> GameResult<FlagResult> {
         if self.status.is_over() {
+            return Ok(FlagResult::GameOver);
+        }
+
+        let cell = self.board.cell(pos)?;
+        if cell.is_revealed() {
+            return Ok(FlagResult::AlreadyRevealed);
+        }
+
+        let next = self.options.mark_cycle.next(cell.mark());
+        self.set_mark(pos, next)?;
+
+        Ok(match next {
+            Mark::None => FlagResult::Unflagged,
+            Mark::Flag => FlagResult::Flagged,
+            Mark::Question => FlagResult::Questioned,
+        })
+    }
+
+    /// Like [`toggle_flag`](Self::toggle_flag), for callers that only need
+    /// to know whether the mark changed.
+    ///
+    /// # Returns
+    /// Returns `true` if the mark changed.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn cycle_mark(&mut self, pos: CellPosition) -> GameResult<bool> {
+        Ok(self.toggle_flag(pos)?.is_toggled())
+    }
+
+    /// Puts the given mark on the hidden cell at the given position, whatever
+    /// mark it had before. Flags count against the mines, question marks don't.
+    ///
+    /// Like flagging, marking is allowed before the first reveal and doesn't
+    /// start the game.
+    ///
+    /// # Returns
+    /// Returns `true` if the mark changed, `false` if the game is over, the
+    /// cell is revealed or it already had that mark.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn set_mark(&mut self, pos: CellPosition, mark: Mark) -> GameResult<bool> {
+        let cell = self.board.cell(pos)?;
+        if self.status.is_over() || cell.is_revealed() || cell.mark() == mark {
             return Ok(false);
         }
+        let snapshot = self.snapshot();
 
-        if self.board.cell(pos)?.is_flagged() {
+        // Clear the old mark first, so the board counters stay in step
+        if self.board.unflag(pos)? {
             self.flagged_cells.remove(&pos);
-            return self.board.unflag(pos);
-        } else if self.board.cell(pos)?.is_hidden() {
-            self.flagged_cells.insert(pos);
-            return self.board.flag(pos);
         }
+        self.board.unquestion(pos)?;
 
-        Ok(false)
-    }
+        match mark {
+            Mark::None => (),
+            Mark::Flag => {
+                self.board.flag(pos)?;
+                self.flagged_cells.insert(pos);
+                self.flags_placed = true;
 
-    #[must_use]
-    pub fn flags_remaining(&self) -> isize {
-        if self.custom_flags_remaining
-            < self.difficulty.mines_count.try_into().unwrap_or(isize::MAX)
-        {
-            self.custom_flags_remaining
-        } else {
-            let mines_count = self.difficulty.mines_count.try_into().unwrap_or(isize::MAX);
-            mines_count - (self.board.flagged_count())
+                if self.options.auto_chord_on_flag {
+                    self.auto_chord_result = Some(self.auto_chord(pos)?);
+                }
+            }
+            Mark::Question => {
+                self.board.question(pos)?;
+            }
         }
+
+        self.clicks += 1;
+        self.generation += 1;
+        self.record(ReplayAction::Mark(mark), Some(pos), snapshot.generation);
+        self.push_undo(snapshot);
+        self.debug_assert_invariants();
+
+        Ok(true)
     }
 
-    #[must_use]
-    pub const fn status(&self) -> &GameStatus {
-        &self.status
+    /// Returns the mark on the cell at the given position.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn mark(&self, pos: CellPosition) -> GameResult<Mark> {
+        Ok(self.board.cell(pos)?.mark())
     }
 
-    #[must_use]
-    pub const fn elapsed_seconds(&self) -> u64 {
-        if self.elapsed_seconds < 999 {
-            self.elapsed_seconds
-        } else {
-            999
-        }
+    /// Returns whether the cell at the given position is flagged.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn is_flagged(&self, pos: CellPosition) -> GameResult<bool> {
+        Ok(self.board.cell(pos)?.is_flagged())
+    }
+
+    /// Returns the cell at the given position, to read its content and state
+    /// without going through [`display_cell`](Self::display_cell).
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn cell(&self, pos: CellPosition) -> GameResult<&Cell> {
+        self.board.cell(pos)
     }
 
+    /// Returns the board being played. Before the first click it has no
+    /// mines yet, only the marks put on it, see [`Board::blank`].
     #[must_use]
-    pub const fn difficulty(&self) -> &GameDifficulty {
-        &self.difficulty
+    pub const fn board(&self) -> &Board {
+        &self.board
     }
 
-    pub fn tick(&mut self) {
-        if self.status.is_in_progress() {
-            if let Some(start_time) = self.start_time {
-                self.elapsed_seconds = start_time.elapsed().as_secs();
+    /// Chords every revealed number around a new flag that the flag satisfies.
+    fn auto_chord(&mut self, flag_pos: CellPosition) -> GameResult<RevealResult> {
+        let adjacent: Vec<CellPosition> = self.board.adjacent_positions(flag_pos).collect();
+        let mut revealed_count = 0;
+        // Assisted before the chords, so a win they give sets no record
+        let was_assisted = self.assisted;
+        self.assisted = true;
+
+        for adj_pos in adjacent {
+            match self.chord(adj_pos)? {
+                RevealResult::GameOver(mine_pos) => return Ok(RevealResult::GameOver(mine_pos)),
+                RevealResult::Continue(count) => revealed_count += count,
+                RevealResult::CantReveal(_) => (),
             }
         }
 
-        if self.custom_flags_remaining
-            < self.difficulty.mines_count.try_into().unwrap_or(isize::MAX)
-        {
-            self.custom_flags_remaining = self.custom_flags_remaining.saturating_add(1);
+        if revealed_count > 0 {
+            Ok(RevealResult::Continue(revealed_count))
+        } else {
+            self.assisted = was_assisted;
+            Ok(RevealResult::CantReveal(CantReveal::NothingToReveal))
         }
     }
 
-    /// Changes the difficulty of the game.
+    /// Records the time of a won game if it beats the best time of its
+    /// difficulty and [`score_category`](Self::score_category). Winning
+    /// already does it, later calls give the same answer without recording
+    /// again. Games that aren't leaderboard eligible never set records.
     ///
-    /// # Arguments
-    /// * `difficulty` - The new difficulty to set
-    ///
-    /// # Errors
-    /// Will return `GameError` if the game cannot be restarted with the new difficulty.
-    pub fn change_difficulty(&mut self, difficulty: GameDifficulty) -> GameResult<()> {
-        if self.difficulty != difficulty {
-            self.difficulty = difficulty;
+    /// # Returns
+    /// Returns `true` if the game set a new best time.
+    pub fn record_best_time(&mut self) -> bool {
+        if let Some(new_record) = self.best_time_recorded {
+            return new_record;
+        }
+        if !self.status.is_won() {
+            return false;
         }
-        self.restart()?;
 
-        Ok(())
+        let new_record = self.is_leaderboard_eligible()
+            && self.best_times.record(
+                &self.difficulty,
+                self.score_category(),
+                self.timer_head_start,
+                self.elapsed_seconds(),
+            );
+        self.best_time_recorded = Some(new_record);
+
+        new_record
     }
 
-    /// Returns the display string for the cell at the given position.
-    ///
-    /// # Arguments
-    /// * `pos` - The position of the cell to display
-    ///
-    /// # Errors
-    /// Will return `GameError` if the position is invalid
-    #[inline]
-    pub fn display_cell(&self, pos: CellPosition) -> GameResult<String> {
-        Ok(self.board.cell(pos)?.to_string())
+    /// Returns `true` once after a win that set a new best time, so the UI
+    /// can save it and celebrate.
+    pub fn take_new_best_time(&mut self) -> bool {
+        std::mem::take(&mut self.new_best_time)
+    }
+
+    /// Returns the status of a game that just ended, once per game, so the
+    /// UI can count it in the statistics. A lost game that is undone and
+    /// finished again is only reported the first time.
+    pub const fn take_finished_status(&mut self) -> Option<GameStatus> {
+        if !self.status.is_over() || self.finish_reported {
+            return None;
+        }
+        self.finish_reported = true;
+
+        Some(self.status)
     }
 
     #[must_use]
-    pub const fn revealed_cells(&self) -> &HashSet<CellPosition> {
-        &self.revealed_cells
+    pub const fn best_times(&self) -> &BestTimes {
+        &self.best_times
     }
 
-    pub fn clear_revealed_cells(&mut self) {
-        self.revealed_cells.clear();
+    /// Replaces the best times, typically with the ones saved on disk.
+    pub fn set_best_times(&mut self, best_times: BestTimes) {
+        self.best_times = best_times;
+    }
+
+    /// Takes the outcome of the auto-chord triggered by the latest flag, if any.
+    pub const fn take_auto_chord_result(&mut self) -> Option<RevealResult> {
+        self.auto_chord_result.take()
     }
 
+    /// Returns the mines count minus the flags placed. It goes negative when
+    /// there are more flags than mines, like the original game. Question
+    /// marks don't count. The mines count is the difficulty's, so it is
+    /// right before the first click places the mines too.
     #[must_use]
-    pub const fn flagged_cells(&self) -> &HashSet<CellPosition> {
-        &self.flagged_cells
+    pub fn flags_remaining(&self) -> isize {
+        let mines_count: isize = self.difficulty.mines_count.try_into().unwrap_or(isize::MAX);
+        mines_count - self.board.flagged_count()
     }
 
-    pub fn clear_flagged_cells(&mut self) {
-        self.flagged_cells.clear();
+    /// Returns whether more flags are placed than there are mines.
+    #[must_use]
+    pub fn is_over_flagged(&self) -> bool {
+        self.flags_remaining() < 0
     }
 
-    pub fn adjacent_positions(&self, pos: CellPosition) -> impl Iterator<Item = CellPosition> + '_ {
-        self.board.adjacent_positions(pos)
+    /// Returns how many cells the first click revealed, or `None` before it.
+    #[must_use]
+    pub const fn first_opening_size(&self) -> Option<usize> {
+        self.first_opening_size
     }
 
-    /// Attempts to reveal all adjacent cells to the given position.
-    ///
-    /// # Errors
-    /// Will return `GameError` if the game is already over.
-    pub fn chording(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
-        if self.status.is_over()
-            || self.board.cell(pos)?.is_hidden()
-            || self.board.cell(pos)?.is_flagged()
-        {
-            return Ok(RevealResult::CantReveal);
-        }
+    /// Returns whether the first click opened a large part of the board.
+    #[must_use]
+    pub fn is_lucky_start(&self) -> bool {
+        let (width, height) = self.difficulty.board_size;
+        self.first_opening_size
+            .is_some_and(|size| size * 100 >= width * height * LUCKY_OPENING_PERCENT)
+    }
 
-        let mut flagged_adjacent = 0;
-        let mut hidden: Vec<CellPosition> = Vec::with_capacity(8);
+    /// Returns a counter that goes up on every change of the cells: reveals,
+    /// marks and restarts. Renderers can skip redrawing while it stays the
+    /// same.
+    #[must_use]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
 
-        for adj_pos in self.board.adjacent_positions(pos) {
-            if self.board.cell(adj_pos)?.is_flagged() {
-                flagged_adjacent += 1;
-            } else if self.board.cell(adj_pos)?.is_hidden() {
-                hidden.push(adj_pos);
-            }
-        }
+    /// Returns whether the cells changed since [`generation`](Self::generation)
+    /// returned `generation`.
+    #[must_use]
+    pub const fn cells_changed_since(&self, generation: u64) -> bool {
+        self.generation != generation
+    }
 
-        if flagged_adjacent != self.board.cell(pos)?.content.as_number() {
-            return Ok(RevealResult::CantReveal);
+    /// Rates how hard the board being played is, see
+    /// [`Solver::estimate_complexity`]. A new game has no board to rate yet.
+    #[must_use]
+    pub fn complexity(&self) -> Option<Complexity> {
+        self.start_cell
+            .map(|start| Solver::estimate_complexity(&self.board, start))
+    }
+
+    /// Returns a hash identifying the mine layout, see [`Board::layout_hash`].
+    /// A new game has no layout yet.
+    #[must_use]
+    pub fn board_hash(&self) -> Option<u64> {
+        (!self.status.is_new()).then(|| self.board.layout_hash())
+    }
+
+    /// Returns the board drawn as text, see [`Board::to_ascii`]. A new game
+    /// has no board yet.
+    #[must_use]
+    pub fn export_ascii(&self) -> Option<String> {
+        (!self.status.is_new()).then(|| self.board.to_ascii())
+    }
+
+    /// Returns the board drawn as text as the player sees it, see
+    /// [`Board::to_player_ascii`].
+    #[must_use]
+    pub fn display_ascii(&self) -> String {
+        self.board.to_player_ascii()
+    }
+
+    /// Returns the seed of the board being played. The board is only
+    /// generated on the first click, so a new game has no seed yet.
+    #[must_use]
+    pub const fn seed(&self) -> Option<u64> {
+        if self.status.is_new() {
+            None
+        } else {
+            Some(self.board.seed())
+        }
+    }
+
+    /// Returns the seed of the current game: the seed of its board once
+    /// generated, or before the first click the seed it was started with.
+    #[must_use]
+    pub fn current_seed(&self) -> Option<u64> {
+        self.seed().or(self.requested_seed)
+    }
+
+    /// Returns the number of cells without a mine the player still has to
+    /// reveal to win.
+    #[must_use]
+    pub fn remaining_safe_cells(&self) -> usize {
+        if self.status.is_new() {
+            let (width, height) = self.difficulty.board_size;
+            return width * height - self.difficulty.mines_count;
+        }
+
+        self.board.remaining_safe_cells()
+    }
+
+    /// Returns the number of question marks on the board. They are counted
+    /// apart from flags and never change [`flags_remaining`](Self::flags_remaining).
+    #[must_use]
+    pub fn questioned_count(&self) -> usize {
+        self.board.questioned_count()
+    }
+
+    #[must_use]
+    pub const fn status(&self) -> &GameStatus {
+        &self.status
+    }
+
+    /// Returns the whole seconds shown on the timer, capped at 999.
+    /// Computed from the start time on every call, so it is always current.
+    #[must_use]
+    pub fn elapsed_seconds(&self) -> u64 {
+        self.elapsed().as_secs().min(999)
+    }
+
+    /// Returns the time played so far, or the total time of a finished game.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    /// Returns the time played as of `now`.
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        let Some(start_time) = self.start_time else {
+            return Duration::ZERO;
+        };
+
+        self.finish_time
+            .or(self.paused_at)
+            .unwrap_or(now)
+            .saturating_duration_since(start_time)
+    }
+
+    /// Stops the timer of a game in progress until [`resume`](Self::resume)
+    /// is called. Does nothing if the game isn't running or already paused.
+    pub fn pause(&mut self) {
+        if self.status.is_in_progress() && self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// Restarts a paused timer. The start time is pushed forward by the
+    /// paused span, so the time played before the pause is kept.
+    pub fn resume(&mut self) {
+        let Some(paused_at) = self.paused_at.take() else {
+            return;
+        };
+
+        if let Some(start_time) = self.start_time.as_mut() {
+            *start_time += paused_at.elapsed();
+        }
+    }
+
+    #[must_use]
+    pub const fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    #[must_use]
+    pub const fn difficulty(&self) -> &GameDifficulty {
+        &self.difficulty
+    }
+
+    /// Returns the 3BV of the board, or `None` before the first click
+    /// generates it.
+    #[must_use]
+    pub fn board_3bv(&self) -> Option<usize> {
+        (!self.status.is_new()).then(|| self.board.calculate_3bv())
+    }
+
+    /// Returns the 3BV cleared per second on the timer, or `None` unless the
+    /// game is won.
+    #[must_use]
+    pub fn three_bv_per_second(&self) -> Option<f64> {
+        if !self.status.is_won() {
+            return None;
+        }
+
+        // A win within the first second counts as taking one
+        let seconds = self.elapsed_seconds().max(1);
+        #[allow(clippy::cast_precision_loss)]
+        let speed = self
+            .board_3bv()
+            .map(|three_bv| three_bv as f64 / seconds as f64);
+        speed
+    }
+
+    /// Returns the summary of a finished game, or `None` while it is still
+    /// being played.
+    #[must_use]
+    pub fn summary(&self) -> Option<GameSummary> {
+        if !self.status.is_over() {
+            return None;
+        }
+
+        let three_bv = self.board.calculate_3bv();
+        #[allow(clippy::cast_precision_loss)]
+        let efficiency = if self.clicks == 0 {
+            0.0
+        } else {
+            three_bv as f64 / self.clicks as f64 * 100.0
+        };
+
+        Some(GameSummary {
+            won: self.status.is_won(),
+            elapsed: self.elapsed(),
+            clicks: self.clicks,
+            three_bv: u32::try_from(three_bv).unwrap_or(u32::MAX),
+            efficiency,
+            wrong_flags: self.wrong_flags_count(),
+            difficulty: self.difficulty,
+            seed: self.board.seed(),
+            timer_head_start: self.timer_head_start,
+            first_opening_size: self.first_opening_size.unwrap_or(0),
+            category: self.score_category(),
+        })
+    }
+
+    /// Returns the summary of a finished game as JSON, ready to paste into
+    /// forums or tools, or `None` while it is still being played.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the summary can't be serialized.
+    pub fn export_summary_json(&self) -> GameResult<Option<String>> {
+        self.summary().map(|summary| summary.to_json()).transpose()
+    }
+
+    /// Saves the game as JSON, so it can be resumed later with
+    /// [`load_from_path`](Self::load_from_path).
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be written.
+    pub fn save_to_path(&self, path: &Path) -> GameResult<()> {
+        let saved_game = SavedGame {
+            board: self.board.clone(),
+            difficulty: self.difficulty,
+            status: self.status,
+            elapsed: self.elapsed(),
+            timer_head_start: self.timer_head_start,
+            clicks: self.clicks,
+            assisted: self.assisted,
+            flags_placed: self.flags_placed,
+            detonated: self.detonated,
+            start_cell: self.start_cell,
+            first_opening_size: self.first_opening_size,
+        };
+        fs::write(path, serde_json::to_string(&saved_game)?)?;
+
+        Ok(())
+    }
+
+    /// Replaces the game with the one saved at `path`. The options and best
+    /// times are kept. The timer goes on from the time saved, paused games
+    /// come back running. A game saved before its first click comes back
+    /// with its marks, and gets its mines from the next click.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be read or doesn't hold a
+    /// consistent game. The current game is kept untouched then.
+    pub fn load_from_path(&mut self, path: &Path) -> GameResult<()> {
+        let saved_game: SavedGame = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Board::validate_difficulty(saved_game.difficulty)?;
+        saved_game.board.check_layout()?;
+        // The first click places the mines, a game saved before it has none
+        let mines_count = if saved_game.status.is_new() {
+            0
+        } else {
+            saved_game.difficulty.mines_count
+        };
+        if saved_game.board.size() != saved_game.difficulty.board_size
+            || saved_game.board.mine_positions().len() != mines_count
+        {
+            return Err(GameError::MalformedBoard(
+                "board doesn't match the difficulty".to_string(),
+            ));
+        }
+
+        self.change_difficulty(saved_game.difficulty)?;
+        let now = Instant::now();
+        if !saved_game.status.is_new() {
+            self.start_time = Some(now.checked_sub(saved_game.elapsed).unwrap_or(now));
+        }
+        if saved_game.status.is_over() {
+            self.finish_time = Some(now);
+        }
+        self.board = saved_game.board;
+        self.rebuild_cell_sets();
+        self.status = saved_game.status;
+        self.timer_head_start = saved_game.timer_head_start;
+        self.clicks = saved_game.clicks;
+        self.assisted = saved_game.assisted;
+        self.flags_placed = saved_game.flags_placed;
+        self.detonated = saved_game.detonated;
+        self.start_cell = saved_game.start_cell;
+        self.first_opening_size = saved_game.first_opening_size;
+        // A game saved after its win must not count as a new record, nor
+        // as one more game played
+        self.best_time_recorded = Some(false);
+        self.finish_reported = saved_game.status.is_over();
+        self.recording = false;
+        self.debug_assert_invariants();
+
+        Ok(())
+    }
+
+    /// Returns the records category of the game. A game on a no-guess board
+    /// counts as no-guess. Otherwise a game won without placing a single
+    /// flag counts as no-flag, even though winning flags the mines.
+    #[must_use]
+    pub const fn score_category(&self) -> ScoreCategory {
+        if self.difficulty.no_guess {
+            ScoreCategory::NoGuess
+        } else if self.flags_placed {
+            ScoreCategory::Standard
+        } else {
+            ScoreCategory::NoFlag
+        }
+    }
+
+    /// Returns what the lost board should show, or `None` unless the game
+    /// is lost.
+    #[must_use]
+    pub fn loss_overlay(&self) -> Option<LossView> {
+        let detonated = self.detonated.filter(|_| self.status.is_lost())?;
+
+        let mut loss_view = LossView {
+            detonated,
+            other_mines: Vec::new(),
+            correct_flags: Vec::new(),
+            wrong_flags: Vec::new(),
+        };
+
+        for (pos, cell) in self.board.iter_cells() {
+            match (cell.is_mine(), cell.is_flagged()) {
+                (true, true) => loss_view.correct_flags.push(pos),
+                (true, false) if pos != detonated => loss_view.other_mines.push(pos),
+                (false, true) => loss_view.wrong_flags.push(pos),
+                _ => (),
+            }
+        }
+
+        Some(loss_view)
+    }
+
+    fn wrong_flags_count(&self) -> usize {
+        self.board
+            .iter_cells()
+            .filter(|(_, cell)| cell.is_flagged() && !cell.is_mine())
+            .count()
+    }
+
+    #[must_use]
+    pub const fn options(&self) -> &GameOptions {
+        &self.options
+    }
+
+    pub const fn set_options(&mut self, options: GameOptions) {
+        self.options = options;
+    }
+
+    /// Returns how many layouts were generated before the current board was
+    /// accepted. Meant for debugging the generators.
+    #[must_use]
+    pub const fn generation_attempts(&self) -> u32 {
+        self.generation_attempts
+    }
+
+    /// Returns whether the current game can set records. Games where an
+    /// assist played moves for the player, a hint was taken, the board is a
+    /// retry or a replay is played back are not eligible.
+    #[must_use]
+    pub const fn is_leaderboard_eligible(&self) -> bool {
+        !self.assisted && self.hints_used == 0 && !self.retried && !self.replaying
+    }
+
+    /// Returns how long after `now` the timer shows its next second, so the
+    /// UI only redraws it when it changes. Returns `None` when the timer is
+    /// stopped: the game isn't running, is paused, or the timer is at 999.
+    #[must_use]
+    pub fn tick(&self, now: Instant) -> Option<Duration> {
+        if !self.status.is_in_progress() || self.is_paused() {
+            return None;
+        }
+
+        let elapsed = self.elapsed_at(now);
+        if elapsed.as_secs() >= 999 {
+            return None;
+        }
+
+        Some(Duration::from_secs(elapsed.as_secs() + 1).saturating_sub(elapsed))
+    }
+
+    /// Changes the difficulty of the game.
+    ///
+    /// # Arguments
+    /// * `difficulty` - The new difficulty to set
+    ///
+    /// # Errors
+    /// Will return `GameError` if no board can be built with the new
+    /// difficulty. The current game and difficulty are kept then.
+    pub fn change_difficulty(&mut self, difficulty: GameDifficulty) -> GameResult<()> {
+        Board::validate_difficulty(difficulty)?;
+        self.difficulty = difficulty;
+        self.restart()
+    }
+
+    /// Restarts the game with the difficulty of the given preset.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the preset is a custom difficulty with an
+    /// invalid board size or mines count. The current game is kept then.
+    pub fn set_preset(&mut self, preset: Preset) -> GameResult<()> {
+        let difficulty = preset.difficulty()?;
+        self.change_difficulty(difficulty)
+    }
+
+    /// Describes how every cell of the board should be drawn, as its index,
+    /// label and CSS classes. Lets the UI redraw the whole board the same way
+    /// whether its cells were just created or are reused.
+    #[must_use]
+    pub fn full_display(&self) -> Vec<(usize, String, Vec<String>)> {
+        let board_size = self.board.size();
+
+        self.board
+            .iter_cells()
+            .filter_map(|(pos, _)| {
+                let (label, css_classes) = self.cell_display(pos).ok()?;
+                Some((pos.to_index(board_size), label, css_classes))
+            })
+            .collect()
+    }
+
+    /// Describes how the cell at the given position should be drawn, as its
+    /// label and CSS classes, like [`full_display`](Self::full_display).
+    ///
+    /// # Errors
+    /// Will return `GameError` if the position is invalid
+    pub fn cell_display(&self, pos: CellPosition) -> GameResult<(String, Vec<String>)> {
+        let cell = self.board.cell(pos)?;
+        let css_classes = if cell.is_revealed() {
+            vec![REVEALED_CELL_CLASS.to_string()]
+        } else {
+            Vec::new()
+        };
+
+        Ok((cell.to_string(), css_classes))
+    }
+
+    /// Returns the display string for the cell at the given position.
+    ///
+    /// # Arguments
+    /// * `pos` - The position of the cell to display
+    ///
+    /// # Errors
+    /// Will return `GameError` if the position is invalid
+    #[inline]
+    pub fn display_cell(&self, pos: CellPosition) -> GameResult<String> {
+        Ok(self.board.cell(pos)?.to_string())
+    }
+
+    #[must_use]
+    pub const fn revealed_cells(&self) -> &HashSet<CellPosition> {
+        &self.revealed_cells
+    }
+
+    pub fn clear_revealed_cells(&mut self) {
+        self.revealed_cells.clear();
+    }
+
+    #[must_use]
+    pub const fn flagged_cells(&self) -> &HashSet<CellPosition> {
+        &self.flagged_cells
+    }
+
+    pub fn clear_flagged_cells(&mut self) {
+        self.flagged_cells.clear();
+    }
+
+    /// Returns the content of the cell opened by the latest reveal action.
+    /// For chording, this is the highest number among the revealed cells.
+    #[must_use]
+    pub const fn last_reveal_content(&self) -> Option<CellContent> {
+        self.last_reveal_content
+    }
+
+    /// Returns the hidden cells adjacent to at least one revealed number,
+    /// ordered by position.
+    #[must_use]
+    pub fn frontier(&self) -> Vec<CellPosition> {
+        let mut frontier: Vec<CellPosition> = self
+            .constraints()
+            .into_iter()
+            .flat_map(|(_, _, hidden)| hidden)
+            .collect::<HashSet<CellPosition>>()
+            .into_iter()
+            .collect();
+        frontier.sort_unstable();
+        frontier
+    }
+
+    /// Returns every revealed number that still touches hidden cells, with the
+    /// count of mines left to find around it (its number minus the adjacent
+    /// flags) and its hidden neighbors.
+    #[must_use]
+    pub fn constraints(&self) -> Vec<(CellPosition, u8, Vec<CellPosition>)> {
+        self.number_constraints(true)
+    }
+
+    /// Same as [`constraints`](Self::constraints), but flags count as hidden
+    /// cells, so the flags themselves can be checked against the numbers.
+    #[must_use]
+    pub fn unflagged_constraints(&self) -> Vec<(CellPosition, u8, Vec<CellPosition>)> {
+        self.number_constraints(false)
+    }
+
+    fn number_constraints(&self, trust_flags: bool) -> Vec<(CellPosition, u8, Vec<CellPosition>)> {
+        let mut constraints = Vec::new();
+
+        for (pos, cell) in self.board.iter_cells() {
+            if !cell.is_revealed() || cell.is_mine() || cell.is_empty() {
+                continue;
+            }
+
+            let mut flagged_adjacent: u8 = 0;
+            let mut hidden: Vec<CellPosition> = Vec::with_capacity(8);
+
+            for adj_pos in self.board.adjacent_positions(pos) {
+                if let Ok(adj_cell) = self.board.cell(adj_pos) {
+                    if adj_cell.is_flagged() && trust_flags {
+                        flagged_adjacent += 1;
+                    } else if !adj_cell.is_revealed() {
+                        hidden.push(adj_pos);
+                    }
+                }
+            }
+
+            if !hidden.is_empty() {
+                let remaining = cell.content.as_number().saturating_sub(flagged_adjacent);
+                constraints.push((pos, remaining, hidden));
+            }
+        }
+
+        constraints
+    }
+
+    /// Returns the chance, from 0 to 1, of a mine under each hidden cell
+    /// without a flag, see [`Solver::mine_probabilities`]. The flags are
+    /// taken as mines. Empty once the game is over.
+    #[must_use]
+    pub fn mine_probabilities(&self) -> HashMap<CellPosition, f64> {
+        if self.status.is_over() {
+            return HashMap::new();
+        }
+
+        let hidden_cells: Vec<CellPosition> = self
+            .board
+            .iter_cells()
+            .filter(|(_, cell)| cell.is_hidden())
+            .map(|(pos, _)| pos)
+            .collect();
+        let flags = usize::try_from(self.board.flagged_count()).unwrap_or(0);
+        let mines_left = self.difficulty.mines_count.saturating_sub(flags);
+
+        Solver::mine_probabilities(self.constraints(), &hidden_cells, mines_left)
+    }
+
+    /// Returns the revealed numbers next to the given cell, with their values.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn neighbors_revealed_numbers(
+        &self,
+        pos: CellPosition,
+    ) -> GameResult<Vec<(CellPosition, u8)>> {
+        self.board.cell(pos)?;
+
+        Ok(self
+            .board
+            .adjacent_positions(pos)
+            .filter_map(|adj_pos| {
+                let cell = self.board.cell(adj_pos).ok()?;
+                (cell.is_revealed() && !cell.is_mine() && !cell.is_empty())
+                    .then(|| (adj_pos, cell.content.as_number()))
+            })
+            .collect())
+    }
+
+    /// Finds a hidden cell that is safe to reveal, and counts it as a hint.
+    /// A number with as many flags around it as its value proves its other
+    /// hidden neighbours safe. Without such a number, any hidden cell without
+    /// a mine is given. A new game gets the center cell, as the first click
+    /// is always safe.
+    ///
+    /// # Returns
+    /// Returns `None` if the game is over or no hidden cell is left.
+    pub fn hint(&mut self) -> Option<CellPosition> {
+        if self.status.is_over() {
+            return None;
+        }
+
+        let (width, height) = self.board.size();
+        let hint = if self.status.is_new() {
+            Some(CellPosition::new(width / 2, height / 2))
+        } else {
+            let positions = || self.board.iter_cells().map(|(pos, _)| pos);
+            // Wrong flags can make a deduction wrong, so every hint is checked
+            let is_safe = |pos: &CellPosition| {
+                self.board
+                    .cell(*pos)
+                    .is_ok_and(|cell| cell.is_hidden() && !cell.is_mine())
+            };
+
+            positions()
+                .filter(|&pos| self.flags_satisfy(pos))
+                .flat_map(|pos| self.board.adjacent_positions(pos))
+                .find(is_safe)
+                .or_else(|| positions().find(is_safe))
+        };
+
+        if hint.is_some() {
+            self.hints_used += 1;
+        }
+        hint
+    }
+
+    /// Plays a move found by the [`Solver`]. Once a move is played the game
+    /// counts as assisted, so it can't set records.
+    ///
+    /// # Returns
+    /// Returns the result of the reveal. A flag placed gives `Continue(0)`,
+    /// and `CantReveal(CantReveal::NothingToReveal)` if it couldn't be placed.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn play_solver_move(&mut self, next_move: Move) -> GameResult<RevealResult> {
+        // Assisted before the move, so a win it gives sets no record
+        let was_assisted = self.assisted;
+        self.assisted = true;
+
+        let result = match next_move {
+            Move::Reveal(pos) => self.reveal_cell(pos),
+            Move::Flag(pos) => self.set_mark(pos, Mark::Flag).map(|placed| {
+                if placed {
+                    RevealResult::Continue(0)
+                } else {
+                    RevealResult::CantReveal(CantReveal::NothingToReveal)
+                }
+            }),
+        };
+        if !matches!(
+            result,
+            Ok(RevealResult::Continue(_) | RevealResult::GameOver(_))
+        ) {
+            self.assisted = was_assisted;
+        }
+
+        result
+    }
+
+    /// Returns how many hints the player took this game.
+    #[must_use]
+    pub const fn hints_used(&self) -> usize {
+        self.hints_used
+    }
+
+    /// Returns whether the cell is a revealed number with as many flags around
+    /// it as its value.
+    fn flags_satisfy(&self, pos: CellPosition) -> bool {
+        let Ok(cell) = self.board.cell(pos) else {
+            return false;
+        };
+        if !cell.is_revealed() || cell.is_empty() {
+            return false;
+        }
+
+        let flags = self
+            .board
+            .adjacent_positions(pos)
+            .filter(|adj_pos| self.board.cell(*adj_pos).is_ok_and(|adj| adj.is_flagged()))
+            .count();
+        flags == usize::from(cell.content.as_number())
+    }
+
+    /// Returns the classic number patterns on the board, see [`Solver::patterns`].
+    #[must_use]
+    pub fn pattern_hints(&self) -> Vec<(Pattern, Vec<CellPosition>)> {
+        Solver::patterns(self)
+    }
+
+    pub fn adjacent_positions(&self, pos: CellPosition) -> impl Iterator<Item = CellPosition> + '_ {
+        self.board.adjacent_positions(pos)
+    }
+
+    /// Returns whether a chord at the given position would reveal something:
+    /// the cell is a revealed number, its flags satisfy it and some of its
+    /// neighbours are still hidden.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn can_chord(&self, pos: CellPosition) -> GameResult<bool> {
+        let cell = self.board.cell(pos)?;
+        if self.status.is_over() || !cell.is_revealed() || cell.is_empty() {
+            return Ok(false);
+        }
+
+        let mut flagged_adjacent = 0;
+        let mut hidden_count = 0;
+        for adj_pos in self.board.adjacent_positions(pos) {
+            if self.board.cell(adj_pos)?.is_flagged() {
+                flagged_adjacent += 1;
+            } else if self.board.cell(adj_pos)?.is_hidden() {
+                hidden_count += 1;
+            }
+        }
+
+        Ok(hidden_count > 0 && flagged_adjacent == cell.content.as_number())
+    }
+
+    /// Does what a single click on the cell most likely means: reveals a
+    /// hidden cell, or chords a revealed number its flags satisfy.
+    ///
+    /// # Returns
+    /// Returns the result of the reveal or of the chord. A revealed cell that
+    /// can't be chorded gives `CantReveal(CantReveal::AlreadyRevealed)`.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn primary_action(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
+        if self.can_chord(pos)? {
+            self.chording(pos)
+        } else {
+            self.reveal_cell(pos)
+        }
+    }
+
+    /// Attempts to reveal all adjacent cells to the given position.
+    /// With [`GameOptions::guard_unsafe_chords`] on, a chord relying on an
+    /// unproven flag returns `CantReveal(CantReveal::UnsafeChord)` instead.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the game is already over.
+    pub fn chording(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
+        if self.options.guard_unsafe_chords && !self.is_chord_safe(pos)? {
+            return Ok(RevealResult::CantReveal(CantReveal::UnsafeChord));
+        }
+
+        let snapshot = self.snapshot();
+        let chord_result = self.chord(pos)?;
+        if !matches!(chord_result, RevealResult::CantReveal(_)) {
+            self.clicks += 1;
+            self.generation += 1;
+        }
+        self.record(ReplayAction::Chord, Some(pos), snapshot.generation);
+        self.push_undo(snapshot);
+        self.debug_assert_invariants();
+
+        Ok(chord_result)
+    }
+
+    /// Chords the number at the given position without guessing. If its
+    /// hidden neighbours must all be mines, they are flagged. If its flags
+    /// already satisfy it, the other neighbours are revealed like a chord.
+    /// Otherwise nothing happens.
+    ///
+    /// The player's flags are taken as mines, so it can only hit a mine when
+    /// one of them is wrong. Using it makes the game leaderboard ineligible.
+    ///
+    /// # Returns
+    /// Returns `Continue(0)` when it only placed flags.
+    ///
+    /// # Errors
+    /// Will return `GameError` if position is invalid
+    pub fn chording_smart(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
+        let cell = self.board.cell(pos)?;
+        if self.status.is_over() || !cell.is_revealed() || cell.is_empty() {
+            return Ok(RevealResult::CantReveal(CantReveal::NothingToReveal));
+        }
+        let number = usize::from(cell.content.as_number());
+        let snapshot = self.snapshot();
+
+        let mut flagged_adjacent = 0;
+        let mut hidden: Vec<CellPosition> = Vec::with_capacity(8);
+
+        for adj_pos in self.board.adjacent_positions(pos) {
+            if self.board.cell(adj_pos)?.is_flagged() {
+                flagged_adjacent += 1;
+            } else if self.board.cell(adj_pos)?.is_hidden() {
+                hidden.push(adj_pos);
+            }
+        }
+
+        // Assisted before the chord, so a win it gives sets no record
+        let was_assisted = self.assisted;
+        self.assisted = true;
+        let smart_result = if hidden.is_empty() {
+            RevealResult::CantReveal(CantReveal::NothingToReveal)
+        } else if flagged_adjacent + hidden.len() == number {
+            for adj_pos in hidden {
+                self.board.flag(adj_pos)?;
+                self.flagged_cells.insert(adj_pos);
+            }
+            self.flags_placed = true;
+            RevealResult::Continue(0)
+        } else {
+            self.chord(pos)?
+        };
+
+        if matches!(smart_result, RevealResult::CantReveal(_)) {
+            self.assisted = was_assisted;
+        } else {
+            self.clicks += 1;
+            self.generation += 1;
+        }
+        self.record(ReplayAction::SmartChord, Some(pos), snapshot.generation);
+        self.push_undo(snapshot);
+        self.debug_assert_invariants();
+
+        Ok(smart_result)
+    }
+
+    /// Chords every revealed number whose flags satisfy it, over and over
+    /// until nothing more opens. After flagging every mine this clears the
+    /// rest of the board in one go. A wrong flag lets a chord hit a mine, and
+    /// the sweep stops right there.
+    ///
+    /// Every chord that opens cells counts as a click. Chords
+    /// [`GameOptions::guard_unsafe_chords`] refuses are skipped.
+    ///
+    /// # Returns
+    /// Returns the total of cells revealed, `GameOver` with the first mine
+    /// hit, or `CantReveal(CantReveal::NothingToReveal)` when no chord opened
+    /// anything.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the board is inconsistent.
+    pub fn sweep(&mut self) -> GameResult<RevealResult> {
+        let snapshot = self.snapshot();
+        let positions: Vec<CellPosition> = self.board.iter_cells().map(|(pos, _)| pos).collect();
+        let mut revealed_count = 0;
+        let mut highest_content: Option<CellContent> = None;
+
+        loop {
+            let mut progressed = false;
+
+            for &pos in &positions {
+                if self.options.guard_unsafe_chords && !self.is_chord_safe(pos)? {
+                    continue;
+                }
+
+                match self.chord(pos)? {
+                    RevealResult::GameOver(mine_pos) => {
+                        self.clicks += 1;
+                        self.generation += 1;
+                        self.record(ReplayAction::Sweep, None, snapshot.generation);
+                        self.push_undo(snapshot);
+                        self.debug_assert_invariants();
+                        return Ok(RevealResult::GameOver(mine_pos));
+                    }
+                    RevealResult::Continue(count) => {
+                        self.clicks += 1;
+                        revealed_count += count;
+                        progressed = true;
+                        highest_content = highest_content
+                            .into_iter()
+                            .chain(self.last_reveal_content)
+                            .max_by_key(|content| content.as_number());
+                    }
+                    RevealResult::CantReveal(_) => (),
+                }
+            }
+
+            if !progressed {
+                break;
+            }
+        }
+
+        self.debug_assert_invariants();
+        if revealed_count == 0 {
+            return Ok(RevealResult::CantReveal(CantReveal::NothingToReveal));
+        }
+
+        self.generation += 1;
+        self.last_reveal_content = highest_content;
+        self.record(ReplayAction::Sweep, None, snapshot.generation);
+        self.push_undo(snapshot);
+        Ok(RevealResult::Continue(revealed_count))
+    }
+
+    /// Returns whether the numbers prove every flag a chord would rely on.
+    /// A chord that wouldn't reveal anything is safe. Finding a chord unsafe
+    /// makes the game leaderboard ineligible, as the guard told the player
+    /// about a wrong flag.
+    fn is_chord_safe(&mut self, pos: CellPosition) -> GameResult<bool> {
+        let cell = self.board.cell(pos)?;
+        if self.status.is_over() || !cell.is_revealed() {
+            return Ok(true);
+        }
+
+        let mut flagged: Vec<CellPosition> = Vec::with_capacity(8);
+        let mut hidden_count = 0;
+        for adj_pos in self.board.adjacent_positions(pos) {
+            if self.board.cell(adj_pos)?.is_flagged() {
+                flagged.push(adj_pos);
+            } else if self.board.cell(adj_pos)?.is_hidden() {
+                hidden_count += 1;
+            }
+        }
+
+        if hidden_count == 0 || flagged.len() != usize::from(cell.content.as_number()) {
+            return Ok(true);
+        }
+
+        let certain_mines = Solver::certain_mines(self);
+        let safe = flagged
+            .iter()
+            .all(|flag_pos| certain_mines.contains(flag_pos));
+        if !safe {
+            self.assisted = true;
+        }
+
+        Ok(safe)
+    }
+
+    fn chord(&mut self, pos: CellPosition) -> GameResult<RevealResult> {
+        if self.status.is_over()
+            || self.board.cell(pos)?.is_hidden()
+            || self.board.cell(pos)?.is_flagged()
+        {
+            return Ok(RevealResult::CantReveal(CantReveal::NothingToReveal));
+        }
+
+        let mut flagged_adjacent = 0;
+        let mut hidden: Vec<CellPosition> = Vec::with_capacity(8);
+
+        for adj_pos in self.board.adjacent_positions(pos) {
+            if self.board.cell(adj_pos)?.is_flagged() {
+                flagged_adjacent += 1;
+            } else if self.board.cell(adj_pos)?.is_hidden() {
+                hidden.push(adj_pos);
+            }
+        }
+
+        if flagged_adjacent != self.board.cell(pos)?.content.as_number() {
+            return Ok(RevealResult::CantReveal(CantReveal::NothingToReveal));
         }
 
         let mut game_over = false;
         let mut end_cell = CellPosition::new(0, 0);
-        let mut revealed = false;
+        let mut revealed_count = 0;
+        let mut highest_content: Option<CellContent> = None;
 
         for adj_pos in hidden {
-            match self.reveal_cell(adj_pos)? {
+            match self.reveal(adj_pos)? {
                 RevealResult::GameOver(mine_pos) => {
                     game_over = true;
                     end_cell = mine_pos;
                 }
-                RevealResult::Continue => revealed = true,
-                RevealResult::CantReveal => (),
+                RevealResult::Continue(count) => revealed_count += count,
+                RevealResult::CantReveal(_) => (),
             }
+
+            highest_content = highest_content
+                .into_iter()
+                .chain(self.last_reveal_content)
+                .max_by_key(|content| content.as_number());
         }
+        self.last_reveal_content = highest_content;
 
         if game_over {
             Ok(RevealResult::GameOver(end_cell))
-        } else if revealed {
-            Ok(RevealResult::Continue)
+        } else if revealed_count > 0 {
+            Ok(RevealResult::Continue(revealed_count))
         } else {
-            Ok(RevealResult::CantReveal)
+            Ok(RevealResult::CantReveal(CantReveal::NothingToReveal))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::models::game::MineDistribution;
+    use crate::game::models::options::MarkCycle;
+
+    #[test]
+    fn toggle_flag_follows_the_mark_cycle() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let pos = CellPosition::new(0, 0);
+        for result in [FlagResult::Flagged, FlagResult::Unflagged] {
+            assert_eq!(game_state.toggle_flag(pos).unwrap(), result);
+        }
+
+        game_state.set_options(GameOptions {
+            mark_cycle: MarkCycle::Three,
+            ..GameOptions::default()
+        });
+        for (result, mark) in [
+            (FlagResult::Flagged, Mark::Flag),
+            (FlagResult::Questioned, Mark::Question),
+            (FlagResult::Unflagged, Mark::None),
+        ] {
+            assert_eq!(game_state.toggle_flag(pos).unwrap(), result);
+            assert_eq!(game_state.mark(pos).unwrap(), mark);
+        }
+    }
+
+    /// Plays random reveals, flags and chords on random boards of up to
+    /// 12x12, checking after each move what every game must keep true.
+    #[test]
+    fn random_games_keep_invariants() {
+        let mut rng = fastrand::Rng::with_seed(1167);
+        let distributions = [
+            MineDistribution::Uniform,
+            MineDistribution::Clustered,
+            MineDistribution::Spread,
+        ];
+
+        for _ in 0..2000 {
+            let (width, height) = (rng.usize(1..=12), rng.usize(1..=12));
+            if width * height < 2 {
+                continue;
+            }
+            let difficulty = GameDifficulty {
+                board_size: (width, height),
+                mines_count: rng.usize(1..width * height),
+                mine_distribution: distributions[rng.usize(..distributions.len())],
+                no_guess: false,
+            };
+            let mines = isize::try_from(difficulty.mines_count).unwrap();
+            let cells = isize::try_from(width * height).unwrap();
+            let safe_cells = width * height - difficulty.mines_count;
+
+            let mut game_state = GameState::new(difficulty).unwrap();
+            game_state.set_options(GameOptions {
+                auto_chord_on_flag: rng.bool(),
+                ..GameOptions::default()
+            });
+
+            for _ in 0..rng.usize(1..60) {
+                // Positions one past the board edge check the errors too
+                let pos = CellPosition::new(rng.usize(..=width), rng.usize(..=height));
+                let _ = match rng.usize(..3) {
+                    0 => game_state.reveal_cell(pos).map(|_| ()),
+                    1 => game_state.toggle_flag(pos).map(|_| ()),
+                    _ => game_state.chording(pos).map(|_| ()),
+                };
+                game_state.clear_revealed_cells();
+
+                let status = game_state.status();
+                assert!(game_state.board.revealed_count() <= safe_cells);
+                assert!((mines - cells..=mines).contains(&game_state.flags_remaining()));
+                assert!(!(status.is_won() && status.is_lost()));
+                assert!(!status.is_won() || game_state.board.revealed_count() == safe_cells);
+            }
+        }
+    }
+
+    /// Right clicks go flag, question mark, hidden. Only the flag lowers
+    /// the counter, the question mark has its own count.
+    #[test]
+    fn question_marks_leave_the_counter_alone() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state.set_options(GameOptions {
+            mark_cycle: MarkCycle::Three,
+            ..GameOptions::default()
+        });
+        let pos = CellPosition::new(0, 0);
+
+        for (mark, flags_remaining, questioned) in [
+            (Mark::Flag, 9, 0),
+            (Mark::Question, 10, 1),
+            (Mark::None, 10, 0),
+            (Mark::Flag, 9, 0),
+        ] {
+            assert!(game_state.cycle_mark(pos).unwrap());
+            assert_eq!(game_state.mark(pos).unwrap(), mark);
+            assert_eq!(game_state.flags_remaining(), flags_remaining);
+            assert_eq!(game_state.questioned_count(), questioned);
+        }
+    }
+
+    #[test]
+    fn flags_before_the_first_click_dont_start_the_game() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let flag_pos = CellPosition::new(1, 1);
+
+        assert_eq!(
+            game_state.toggle_flag(flag_pos).unwrap(),
+            FlagResult::Flagged
+        );
+        assert!(game_state.status().is_new());
+        assert_eq!(game_state.elapsed_seconds(), 0);
+
+        game_state.reveal_cell(CellPosition::new(5, 5)).unwrap();
+        assert!(!game_state.status().is_new());
+        assert_eq!(
+            game_state.toggle_flag(CellPosition::new(5, 5)).unwrap(),
+            FlagResult::AlreadyRevealed
+        );
+        assert_eq!(
+            game_state.toggle_flag(flag_pos).unwrap(),
+            FlagResult::Unflagged
+        );
+    }
+
+    /// Returns a running game on a 3x1 board with a mine on the left and
+    /// only the middle cell revealed.
+    fn near_complete_game() -> GameState {
+        let board = Board::with_mines((3, 1), HashSet::from([CellPosition::new(0, 0)])).unwrap();
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state.load_board(board).unwrap();
+        game_state.board.reveal(CellPosition::new(1, 0)).unwrap();
+        game_state.status = GameStatus::InProgress;
+        game_state
+    }
+
+    #[test]
+    fn evaluate_win_wins_a_completed_board_once() {
+        let mut game_state = near_complete_game();
+        assert!(!game_state.evaluate_win());
+        assert!(game_state.status().is_in_progress());
+
+        // Revealed behind the game's back, as another win path would
+        game_state.board.reveal(CellPosition::new(2, 0)).unwrap();
+        assert!(game_state.evaluate_win());
+        assert!(game_state.status().is_won());
+        assert!(game_state.evaluate_win());
+        assert!(game_state.status().is_won());
+    }
+
+    #[test]
+    fn evaluate_win_keeps_a_loss() {
+        let mut game_state = near_complete_game();
+        game_state.reveal_cell(CellPosition::new(0, 0)).unwrap();
+        assert!(game_state.status().is_lost());
+
+        game_state.board.reveal(CellPosition::new(2, 0)).unwrap();
+        assert!(!game_state.evaluate_win());
+        assert!(game_state.status().is_lost());
+    }
+
+    #[test]
+    fn corner_flood_reports_the_cells_revealed() {
+        // A wall of mines down the middle column: the left opening is the
+        // empty column and the numbers beside it
+        let mines = (0..5).map(|y| CellPosition::new(2, y)).collect();
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((5, 5), mines).unwrap())
+            .unwrap();
+
+        let result = game_state.reveal_cell(CellPosition::new(0, 0)).unwrap();
+        assert!(matches!(result, RevealResult::Continue(10)));
+        assert_eq!(game_state.revealed_cells().len(), 10);
+
+        game_state.clear_revealed_cells();
+        let result = game_state.reveal_cell(CellPosition::new(3, 0)).unwrap();
+        assert!(matches!(result, RevealResult::Continue(1)));
+    }
+
+    #[test]
+    fn set_mark_moves_between_every_mark() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let pos = CellPosition::new(3, 3);
+        let marks = [Mark::None, Mark::Flag, Mark::Question];
+
+        for from in marks {
+            for to in marks {
+                game_state.set_mark(pos, from).unwrap();
+                assert_eq!(game_state.set_mark(pos, to).unwrap(), from != to);
+                assert_eq!(game_state.mark(pos).unwrap(), to);
+                assert_eq!(game_state.is_flagged(pos).unwrap(), to == Mark::Flag);
+                assert_eq!(game_state.flagged_cells.contains(&pos), to == Mark::Flag);
+                assert_eq!(
+                    game_state.questioned_count(),
+                    usize::from(to == Mark::Question)
+                );
+                assert_eq!(
+                    game_state.flags_remaining(),
+                    10 - isize::from(to == Mark::Flag)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn set_mark_refuses_revealed_cells() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let pos = CellPosition::new(4, 4);
+        game_state.reveal_cell(pos).unwrap();
+
+        for mark in [Mark::Flag, Mark::Question] {
+            assert!(!game_state.set_mark(pos, mark).unwrap());
+            assert_eq!(game_state.mark(pos).unwrap(), Mark::None);
+        }
+    }
+
+    #[test]
+    fn loss_overlay_sorts_the_mines_and_flags() {
+        let mines = [(0, 0), (2, 0), (3, 0)]
+            .into_iter()
+            .map(|(x, y)| CellPosition::new(x, y))
+            .collect();
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((4, 3), mines).unwrap())
+            .unwrap();
+        game_state.reveal_cell(CellPosition::new(1, 2)).unwrap();
+        assert_eq!(game_state.loss_overlay(), None);
+
+        game_state
+            .set_mark(CellPosition::new(1, 0), Mark::Flag)
+            .unwrap();
+        game_state
+            .set_mark(CellPosition::new(2, 0), Mark::Flag)
+            .unwrap();
+        game_state.reveal_cell(CellPosition::new(0, 0)).unwrap();
+        assert!(game_state.status().is_lost());
+
+        assert_eq!(
+            game_state.loss_overlay(),
+            Some(LossView {
+                detonated: CellPosition::new(0, 0),
+                other_mines: vec![CellPosition::new(3, 0)],
+                correct_flags: vec![CellPosition::new(2, 0)],
+                wrong_flags: vec![CellPosition::new(1, 0)],
+            })
+        );
+    }
+
+    #[test]
+    fn flags_remaining_counts_flags_against_the_mines() {
+        let game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        assert_eq!(game_state.flags_remaining(), 10);
+
+        let mines = HashSet::from([CellPosition::new(0, 0), CellPosition::new(2, 0)]);
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((3, 3), mines).unwrap())
+            .unwrap();
+        assert_eq!(game_state.flags_remaining(), 2);
+
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        assert_eq!(game_state.flags_remaining(), 1);
+
+        // Wrong flags count as well, past zero
+        let wrong_flags = [(1, 0), (0, 1), (1, 1)].map(|(x, y)| CellPosition::new(x, y));
+        for pos in wrong_flags {
+            game_state.set_mark(pos, Mark::Flag).unwrap();
+        }
+        assert_eq!(game_state.flags_remaining(), -2);
+        assert!(game_state.is_over_flagged());
+
+        for pos in wrong_flags {
+            game_state.set_mark(pos, Mark::None).unwrap();
+        }
+        game_state.reveal_cell(CellPosition::new(1, 2)).unwrap();
+        game_state.reveal_cell(CellPosition::new(1, 0)).unwrap();
+        assert!(game_state.status().is_won());
+        assert_eq!(game_state.flags_remaining(), 0);
+        assert!(!game_state.is_over_flagged());
+    }
+
+    #[test]
+    fn cant_reveal_says_why() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let flagged = CellPosition::new(0, 0);
+        game_state.set_mark(flagged, Mark::Flag).unwrap();
+        assert!(matches!(
+            game_state.reveal_cell(flagged).unwrap(),
+            RevealResult::CantReveal(CantReveal::Flagged)
+        ));
+
+        let revealed = CellPosition::new(4, 4);
+        game_state.reveal_cell(revealed).unwrap();
+        assert!(matches!(
+            game_state.reveal_cell(revealed).unwrap(),
+            RevealResult::CantReveal(CantReveal::AlreadyRevealed)
+        ));
+        assert!(matches!(
+            game_state.board.reveal(flagged).unwrap(),
+            RevealResult::CantReveal(CantReveal::Flagged)
+        ));
+        assert!(matches!(
+            game_state.board.reveal(revealed).unwrap(),
+            RevealResult::CantReveal(CantReveal::AlreadyRevealed)
+        ));
+
+        // A question mark doesn't stop a reveal
+        let questioned = game_state
+            .board
+            .iter_cells()
+            .find(|(_, cell)| cell.is_hidden() && !cell.is_mine() && !cell.is_flagged())
+            .map(|(pos, _)| pos)
+            .unwrap();
+        game_state.set_mark(questioned, Mark::Question).unwrap();
+        assert!(matches!(
+            game_state.reveal_cell(questioned).unwrap(),
+            RevealResult::Continue(_)
+        ));
+    }
+
+    #[test]
+    fn marks_before_the_first_click_survive_it() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        // A seed where the first click doesn't open the marked cells
+        game_state.restart_with_seed(Some(1207)).unwrap();
+        let flag = CellPosition::new(0, 0);
+        let question = CellPosition::new(8, 8);
+        game_state.set_mark(flag, Mark::Flag).unwrap();
+        game_state.set_mark(question, Mark::Question).unwrap();
+
+        game_state.reveal_cell(CellPosition::new(4, 4)).unwrap();
+        assert!(game_state.status().is_in_progress());
+        assert_eq!(game_state.mark(flag).unwrap(), Mark::Flag);
+        assert_eq!(game_state.mark(question).unwrap(), Mark::Question);
+        assert_eq!(game_state.questioned_count(), 1);
+        assert_eq!(game_state.flags_remaining(), 9);
+    }
+
+    #[test]
+    fn two_mark_cycle_skips_question_marks() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        assert_eq!(game_state.options().mark_cycle, MarkCycle::Two);
+        let pos = CellPosition::new(0, 0);
+
+        for (mark, flags_remaining) in [(Mark::Flag, 9), (Mark::None, 10), (Mark::Flag, 9)] {
+            assert!(game_state.cycle_mark(pos).unwrap());
+            assert_eq!(game_state.mark(pos).unwrap(), mark);
+            assert_eq!(game_state.flags_remaining(), flags_remaining);
+            assert_eq!(game_state.questioned_count(), 0);
+        }
+
+        // A question mark set directly counts as no mark
+        game_state.set_mark(pos, Mark::Question).unwrap();
+        assert!(game_state.cycle_mark(pos).unwrap());
+        assert_eq!(game_state.mark(pos).unwrap(), Mark::Flag);
+        assert_eq!(game_state.questioned_count(), 0);
+    }
+
+    #[test]
+    fn generation_moves_on_changes_only() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let flag = CellPosition::new(0, 0);
+        let start = game_state.generation();
+
+        game_state.set_mark(flag, Mark::Flag).unwrap();
+        assert!(game_state.cells_changed_since(start));
+        let flagged = game_state.generation();
+
+        let _ = game_state.frontier();
+        let _ = game_state.constraints();
+        let _ = game_state.full_display();
+        let _ = game_state.elapsed();
+        assert!(!game_state.cells_changed_since(flagged));
+
+        // Refused reveals change nothing
+        game_state.reveal_cell(flag).unwrap();
+        assert_eq!(game_state.generation(), flagged);
+
+        game_state.reveal_cell(CellPosition::new(4, 4)).unwrap();
+        assert!(game_state.generation() > flagged);
+        let revealed = game_state.generation();
+
+        game_state.restart().unwrap();
+        assert!(game_state.generation() > revealed);
+    }
+
+    #[test]
+    fn new_games_keep_the_difficulty() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .change_difficulty(GameDifficulty::EXPERT)
+            .unwrap();
+
+        for seed in 0..6 {
+            game_state.reveal_cell(CellPosition::new(10, 10)).unwrap();
+            match seed % 3 {
+                0 => game_state.restart().unwrap(),
+                1 => game_state.restart_with_seed(Some(seed)).unwrap(),
+                _ => game_state.restart_same_board().unwrap(),
+            }
+
+            assert_eq!(*game_state.difficulty(), GameDifficulty::EXPERT);
+            assert_eq!(game_state.board().size(), (30, 16));
+            assert_eq!(game_state.board().iter_cells().count(), 30 * 16);
+        }
+    }
+
+    /// Returns a game on a 3x3 board with mines in the top corners, the two
+    /// lower rows revealed and the top middle cell left to clear.
+    fn top_corners_game() -> GameState {
+        let mines = HashSet::from([CellPosition::new(0, 0), CellPosition::new(2, 0)]);
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((3, 3), mines).unwrap())
+            .unwrap();
+        game_state.reveal_cell(CellPosition::new(0, 2)).unwrap();
+        game_state
+    }
+
+    #[test]
+    fn sweep_clears_a_correctly_flagged_board() {
+        let mut game_state = top_corners_game();
+        assert!(matches!(
+            game_state.sweep().unwrap(),
+            RevealResult::CantReveal(CantReveal::NothingToReveal)
+        ));
+
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        game_state
+            .set_mark(CellPosition::new(2, 0), Mark::Flag)
+            .unwrap();
+        assert!(matches!(
+            game_state.sweep().unwrap(),
+            RevealResult::Continue(1)
+        ));
+        assert!(game_state.status().is_won());
+    }
+
+    #[test]
+    fn sweep_stops_on_the_mine_a_wrong_flag_uncovers() {
+        let mut game_state = top_corners_game();
+        game_state
+            .set_mark(CellPosition::new(1, 0), Mark::Flag)
+            .unwrap();
+        game_state
+            .set_mark(CellPosition::new(2, 0), Mark::Flag)
+            .unwrap();
+
+        let result = game_state.sweep().unwrap();
+        assert!(matches!(
+            result,
+            RevealResult::GameOver(pos) if pos == CellPosition::new(0, 0)
+        ));
+        assert!(game_state.status().is_lost());
+    }
+
+    #[test]
+    fn primary_action_reveals_hidden_cells() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let pos = CellPosition::new(4, 4);
+        assert!(matches!(
+            game_state.primary_action(pos).unwrap(),
+            RevealResult::Continue(_)
+        ));
+        assert!(game_state.cell(pos).unwrap().is_revealed());
+    }
+
+    #[test]
+    fn primary_action_chords_satisfied_numbers_only() {
+        let mut game_state = top_corners_game();
+        let number = CellPosition::new(0, 1);
+        assert!(matches!(
+            game_state.primary_action(number).unwrap(),
+            RevealResult::CantReveal(CantReveal::AlreadyRevealed)
+        ));
+        assert!(game_state
+            .cell(CellPosition::new(1, 0))
+            .unwrap()
+            .is_hidden());
+
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        assert!(matches!(
+            game_state.primary_action(number).unwrap(),
+            RevealResult::Continue(1)
+        ));
+        assert!(game_state
+            .cell(CellPosition::new(1, 0))
+            .unwrap()
+            .is_revealed());
+    }
+
+    #[test]
+    fn score_category_follows_the_board_and_the_flags() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        assert_eq!(game_state.score_category(), ScoreCategory::NoFlag);
+
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        assert_eq!(game_state.score_category(), ScoreCategory::Standard);
+
+        game_state
+            .change_difficulty(GameDifficulty {
+                no_guess: true,
+                ..GameDifficulty::BEGINNER
+            })
+            .unwrap();
+        assert_eq!(game_state.score_category(), ScoreCategory::NoGuess);
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        assert_eq!(game_state.score_category(), ScoreCategory::NoGuess);
+    }
+
+    #[test]
+    fn loading_a_saved_game_restores_the_marked_and_revealed_cells() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty-minesweeper-{}-saved-game.json",
+            std::process::id()
+        ));
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state.restart_with_seed(Some(1255)).unwrap();
+        game_state.reveal_cell(CellPosition::new(4, 4)).unwrap();
+        let mine = *game_state.board().mine_positions().iter().min().unwrap();
+        game_state.set_mark(mine, Mark::Flag).unwrap();
+        game_state.save_to_path(&path).unwrap();
+
+        let mut loaded = GameState::new(GameDifficulty::EXPERT).unwrap();
+        loaded.load_from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.flagged_cells(), &HashSet::from([mine]));
+        let revealed: HashSet<CellPosition> = game_state
+            .board()
+            .iter_cells()
+            .filter(|(_, cell)| cell.is_revealed())
+            .map(|(pos, _)| pos)
+            .collect();
+        assert!(!revealed.is_empty());
+        assert_eq!(loaded.revealed_cells(), &revealed);
+    }
+
+    #[test]
+    fn loading_a_board_keeps_its_marked_and_revealed_cells() {
+        let board = Board::from_ascii("F1#\n11#\n###\n").unwrap();
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .set_mark(CellPosition::new(5, 5), Mark::Flag)
+            .unwrap();
+        game_state.load_board(board).unwrap();
+
+        assert!(game_state.status().is_in_progress());
+        assert_eq!(
+            game_state.flagged_cells(),
+            &HashSet::from([CellPosition::new(0, 0)])
+        );
+        let revealed = [(1, 0), (0, 1), (1, 1)].map(|(x, y)| CellPosition::new(x, y));
+        assert_eq!(game_state.revealed_cells(), &HashSet::from(revealed));
+    }
+
+    #[test]
+    fn flags_remaining_starts_over_with_a_new_game() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        for x in 0..9 {
+            for y in 0..2 {
+                game_state
+                    .set_mark(CellPosition::new(x, y), Mark::Flag)
+                    .unwrap();
+            }
+        }
+        assert_eq!(game_state.flags_remaining(), -8);
+
+        game_state.restart().unwrap();
+        assert_eq!(game_state.flags_remaining(), 10);
+
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        game_state
+            .change_difficulty(GameDifficulty::EXPERT)
+            .unwrap();
+        assert_eq!(game_state.flags_remaining(), 99);
+        assert!(!game_state.is_over_flagged());
+    }
+
+    /// Plays a seeded no-guess Beginner game to the end with the solver's
+    /// moves, the last one through `play_solver_move` if `assist_last`.
+    fn solve_beginner(assist_last: bool) -> GameState {
+        let mut game_state = GameState::new(GameDifficulty {
+            no_guess: true,
+            ..GameDifficulty::BEGINNER
+        })
+        .unwrap();
+        game_state.restart_with_seed(Some(1279)).unwrap();
+        game_state.reveal_cell(CellPosition::new(4, 4)).unwrap();
+
+        while let Some(next_move) = Solver::next_move(&game_state) {
+            let last = game_state.board().remaining_safe_cells() == 1;
+            match next_move {
+                Move::Reveal(_) if last && assist_last => {
+                    game_state.play_solver_move(next_move).unwrap();
+                }
+                Move::Reveal(pos) => {
+                    game_state.reveal_cell(pos).unwrap();
+                }
+                Move::Flag(pos) => {
+                    game_state.set_mark(pos, Mark::Flag).unwrap();
+                }
+            }
+        }
+        assert!(game_state.status().is_won());
+        game_state
+    }
+
+    #[test]
+    fn a_win_by_the_solver_sets_no_record() {
+        let mut game_state = solve_beginner(false);
+        assert!(game_state.take_new_best_time());
+
+        let mut game_state = solve_beginner(true);
+        assert!(!game_state.is_leaderboard_eligible());
+        assert!(!game_state.take_new_best_time());
+        assert!(!game_state.record_best_time());
+        assert!(game_state
+            .best_times()
+            .best(
+                game_state.difficulty(),
+                game_state.score_category(),
+                game_state.timer_head_start,
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn a_refused_solver_move_is_no_assist() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        let pos = CellPosition::new(4, 4);
+        game_state.reveal_cell(pos).unwrap();
+
+        assert!(matches!(
+            game_state.play_solver_move(Move::Reveal(pos)).unwrap(),
+            RevealResult::CantReveal(_)
+        ));
+        assert!(game_state.is_leaderboard_eligible());
+    }
+
+    #[test]
+    fn first_click_opening_keeps_mines_off_the_neighbors() {
+        let mut rng = fastrand::Rng::with_seed(1281);
+        let mut game_state = GameState::new(GameDifficulty::EXPERT).unwrap();
+        game_state.set_options(GameOptions {
+            first_click_opening: true,
+            ..GameOptions::default()
+        });
+
+        for _ in 0..300 {
+            game_state.restart_with_seed(Some(rng.u64(..))).unwrap();
+            let first = CellPosition::new(rng.usize(..30), rng.usize(..16));
+            assert!(matches!(
+                game_state.reveal_cell(first).unwrap(),
+                RevealResult::Continue(_)
+            ));
+
+            assert!(game_state.cell(first).unwrap().is_empty());
+            for pos in game_state.board().adjacent_positions(first) {
+                assert!(!game_state.cell(pos).unwrap().is_mine(), "{pos:?}");
+                assert!(game_state.cell(pos).unwrap().is_revealed());
+            }
+        }
+    }
+
+    #[test]
+    fn flags_before_the_first_click_survive_it() {
+        let flags = [
+            CellPosition::new(0, 0),
+            CellPosition::new(8, 0),
+            CellPosition::new(0, 8),
+            CellPosition::new(8, 8),
+        ];
+        for seed in 0..200 {
+            let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+            game_state.restart_with_seed(Some(seed)).unwrap();
+            for pos in flags {
+                assert_eq!(game_state.toggle_flag(pos).unwrap(), FlagResult::Flagged);
+            }
+
+            game_state.reveal_cell(CellPosition::new(4, 4)).unwrap();
+            for pos in flags {
+                assert_eq!(game_state.mark(pos).unwrap(), Mark::Flag);
+            }
+            assert_eq!(game_state.flagged_cells, HashSet::from(flags));
+            assert_eq!(game_state.flags_remaining(), 6);
+        }
+    }
+
+    #[test]
+    fn too_many_mines_keep_the_current_game() {
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state.restart_with_seed(Some(1289)).unwrap();
+        game_state.reveal_cell(CellPosition::new(4, 4)).unwrap();
+        let flag = game_state
+            .board
+            .iter_cells()
+            .find(|(_, cell)| !cell.is_revealed())
+            .map(|(pos, _)| pos)
+            .unwrap();
+        game_state.toggle_flag(flag).unwrap();
+        let board = game_state.board.to_ascii();
+
+        for mines_count in [81, 82] {
+            let difficulty = GameDifficulty {
+                mines_count,
+                ..GameDifficulty::BEGINNER
+            };
+            assert!(game_state.change_difficulty(difficulty).is_err());
+            assert_eq!(game_state.difficulty(), &GameDifficulty::BEGINNER);
+            assert_eq!(game_state.board.to_ascii(), board);
+            assert_eq!(game_state.board.size(), (9, 9));
+            assert_eq!(game_state.board.mine_positions().len(), 10);
+            assert!(game_state.status().is_in_progress());
+            assert_eq!(game_state.mark(flag).unwrap(), Mark::Flag);
+        }
+    }
+
+    /// Returns a Beginner game that can set records, with every safe cell
+    /// revealed but the top one between the mines at (0, 0) and (2, 0).
+    fn beginner_pocket_game() -> GameState {
+        let mut mines: HashSet<_> = (0..9)
+            .filter(|&x| x != 1)
+            .map(|x| CellPosition::new(x, 0))
+            .collect();
+        mines.extend([CellPosition::new(7, 1), CellPosition::new(8, 1)]);
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state
+            .load_board(Board::with_mines((9, 9), mines).unwrap())
+            .unwrap();
+        game_state.reveal_cell(CellPosition::new(4, 8)).unwrap();
+        assert_eq!(game_state.board.remaining_safe_cells(), 1);
+        // A loaded board can't set records, so play it as a fresh one
+        game_state.retried = false;
+        game_state
+    }
+
+    #[test]
+    fn a_win_by_a_chord_of_the_game_sets_no_record() {
+        let (left, right) = (CellPosition::new(0, 0), CellPosition::new(2, 0));
+        let mut game_state = beginner_pocket_game();
+        game_state.set_mark(left, Mark::Flag).unwrap();
+        game_state.set_mark(right, Mark::Flag).unwrap();
+        game_state.reveal_cell(CellPosition::new(1, 0)).unwrap();
+        assert!(game_state.status().is_won());
+        assert!(game_state.take_new_best_time());
+
+        // The second flag satisfies the 2 below the pocket, whose chord wins
+        let mut game_state = beginner_pocket_game();
+        game_state.set_options(GameOptions {
+            auto_chord_on_flag: true,
+            ..GameOptions::default()
+        });
+        game_state.set_mark(left, Mark::Flag).unwrap();
+        game_state.set_mark(right, Mark::Flag).unwrap();
+        assert!(game_state.status().is_won());
+        assert!(!game_state.take_new_best_time());
+
+        let mut game_state = beginner_pocket_game();
+        game_state.set_mark(left, Mark::Flag).unwrap();
+        game_state.set_mark(right, Mark::Flag).unwrap();
+        game_state.chording_smart(CellPosition::new(1, 1)).unwrap();
+        assert!(game_state.status().is_won());
+        assert!(!game_state.take_new_best_time());
+        assert!(!game_state.record_best_time());
+    }
+
+    #[test]
+    fn nothing_is_revealed_after_the_game_is_over() {
+        let mut game_state = top_corners_game();
+        let hidden = CellPosition::new(1, 0);
+        game_state.reveal_cell(CellPosition::new(0, 0)).unwrap();
+        assert!(game_state.status().is_lost());
+        let board = game_state.board.to_ascii();
+        let (generation, undo_depth) = (game_state.generation(), game_state.undo_stack.len());
+
+        for result in [
+            game_state.reveal_cell(hidden).unwrap(),
+            game_state.primary_action(hidden).unwrap(),
+            game_state.primary_action(CellPosition::new(1, 1)).unwrap(),
+        ] {
+            assert!(matches!(
+                result,
+                RevealResult::CantReveal(CantReveal::NothingToReveal)
+            ));
         }
+        assert!(game_state.cell(hidden).unwrap().is_hidden());
+        assert_eq!(game_state.board.to_ascii(), board);
+        assert_eq!(game_state.generation(), generation);
+        assert_eq!(game_state.undo_stack.len(), undo_depth);
+    }
+
+    #[test]
+    fn a_game_saved_before_the_first_click_loads_back() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty-minesweeper-{}-unstarted-game.json",
+            std::process::id()
+        ));
+        let (flag, question) = (CellPosition::new(0, 0), CellPosition::new(8, 8));
+        let mut game_state = GameState::new(GameDifficulty::BEGINNER).unwrap();
+        game_state.set_mark(flag, Mark::Flag).unwrap();
+        game_state.set_mark(question, Mark::Question).unwrap();
+        game_state.save_to_path(&path).unwrap();
+
+        let mut loaded = GameState::new(GameDifficulty::EXPERT).unwrap();
+        loaded.load_from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.status().is_new());
+        assert_eq!(loaded.difficulty(), &GameDifficulty::BEGINNER);
+        assert!(loaded.board().mine_positions().is_empty());
+        assert_eq!(loaded.flagged_cells(), &HashSet::from([flag]));
+        assert_eq!(loaded.mark(question).unwrap(), Mark::Question);
+        assert_eq!(loaded.flags_remaining(), 9);
+
+        loaded.reveal_cell(CellPosition::new(4, 4)).unwrap();
+        assert!(!loaded.status().is_new());
+        assert_eq!(loaded.board().mine_positions().len(), 10);
+        assert_eq!(loaded.mark(flag).unwrap(), Mark::Flag);
+    }
+
+    #[test]
+    fn only_an_unsafe_chord_costs_the_record() {
+        let guarded = GameOptions {
+            guard_unsafe_chords: true,
+            ..GameOptions::default()
+        };
+
+        // The numbers prove both flags, so the chord is safe and wins
+        let mut game_state = beginner_pocket_game();
+        game_state.set_options(guarded);
+        game_state
+            .set_mark(CellPosition::new(0, 0), Mark::Flag)
+            .unwrap();
+        game_state
+            .set_mark(CellPosition::new(2, 0), Mark::Flag)
+            .unwrap();
+        game_state.chording(CellPosition::new(1, 1)).unwrap();
+        assert!(game_state.status().is_won());
+        assert!(game_state.take_new_best_time());
+
+        // A flag on the safe cell leaves the 1 beside it unproven
+        let mut game_state = beginner_pocket_game();
+        game_state.set_options(guarded);
+        game_state
+            .set_mark(CellPosition::new(1, 0), Mark::Flag)
+            .unwrap();
+        assert!(matches!(
+            game_state.chording(CellPosition::new(0, 1)).unwrap(),
+            RevealResult::CantReveal(CantReveal::UnsafeChord)
+        ));
+        assert!(!game_state.is_leaderboard_eligible());
     }
 }
@@ -0,0 +1,230 @@
+use crate::error::GameResult;
+use crate::game::models::board::{CantReveal, FlagResult, RevealResult};
+use crate::game::models::cell::CellPosition;
+use crate::game::models::game::{GameDifficulty, GameStatus, Preset};
+use crate::game::state::GameState;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+/// Command-line flag that plays without the GUI.
+pub const HEADLESS_FLAG: &str = "--headless";
+/// Exit code when the input ends, or `quit` is read, before the game is over.
+const UNFINISHED_EXIT_CODE: u8 = 2;
+/// Exit code when the command line can't be understood.
+const USAGE_EXIT_CODE: u8 = 64;
+
+const USAGE: &str = "Usage: rusty-minesweeper --headless \
+[--difficulty beginner|intermediate|expert|LEVEL] [--seed SEED]";
+const HELP: &str = "Commands, with X the column and Y the row counted from 0:
+  reveal X Y  reveal a cell
+  flag X Y    flag a cell, or take its flag off
+  chord X Y   reveal around a number its flags satisfy
+  print       draw the board
+  help        show this help
+  quit        give up the game
+Board: # hidden, F flag, * mine, . or 1-8 revealed";
+
+/// What a headless game is started with, read from the command line.
+struct HeadlessOptions {
+    difficulty: GameDifficulty,
+    seed: Option<u64>,
+}
+
+enum Command {
+    Reveal(CellPosition),
+    Flag(CellPosition),
+    Chord(CellPosition),
+    Print,
+    Help,
+    Quit,
+}
+
+/// Plays a game on stdin and stdout with the options in `args`, the
+/// command-line arguments without the program name.
+///
+/// The exit code tells how the game ended: success for a win, failure for a
+/// loss, 2 for a game left unfinished, and 64 for a bad command line.
+#[must_use]
+pub fn main(args: &[String]) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return ExitCode::from(USAGE_EXIT_CODE);
+        }
+    };
+
+    match run(
+        options.difficulty,
+        options.seed,
+        io::stdin().lock(),
+        io::stdout().lock(),
+    ) {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Plays one game of `difficulty`, reading commands from `input`, one per
+/// line, and answering on `output`.
+///
+/// Every move is answered with the board as the player sees it and a status
+/// line. The game ends when it is won or lost, on `quit`, or when the input
+/// ends.
+///
+/// # Errors
+/// Will return `GameError` if the difficulty is invalid, or reading or
+/// writing fails.
+pub fn run(
+    difficulty: GameDifficulty,
+    seed: Option<u64>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> GameResult<ExitCode> {
+    let mut game_state = GameState::new(difficulty)?;
+    game_state.restart_with_seed(seed)?;
+    writeln!(output, "{}", describe_status(&game_state))?;
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_command(&line) {
+            Ok(Command::Print) => write!(output, "{}", game_state.display_ascii())?,
+            Ok(Command::Help) => writeln!(output, "{HELP}")?,
+            Ok(Command::Quit) => break,
+            Ok(command) => match play(&mut game_state, &command) {
+                Ok(refused) => {
+                    if let Some(reason) = refused {
+                        writeln!(output, "{reason}")?;
+                    }
+                    write!(output, "{}", game_state.display_ascii())?;
+                    writeln!(output, "{}", describe_status(&game_state))?;
+                }
+                Err(err) => writeln!(output, "error: {err}")?,
+            },
+            Err(message) => writeln!(output, "error: {message}")?,
+        }
+        output.flush()?;
+
+        if game_state.status().is_over() {
+            break;
+        }
+    }
+
+    Ok(match game_state.status() {
+        GameStatus::Won => ExitCode::SUCCESS,
+        GameStatus::Lost => ExitCode::FAILURE,
+        GameStatus::InProgress | GameStatus::New => ExitCode::from(UNFINISHED_EXIT_CODE),
+    })
+}
+
+fn parse_args(args: &[String]) -> Result<HeadlessOptions, String> {
+    let mut options = HeadlessOptions {
+        difficulty: GameDifficulty::default(),
+        seed: None,
+    };
+    let mut args = args.iter().map(String::as_str);
+
+    while let Some(arg) = args.next() {
+        match arg {
+            HEADLESS_FLAG => (),
+            "--difficulty" => {
+                let preset = match args.next() {
+                    Some("beginner") => Preset::Beginner,
+                    Some("intermediate") => Preset::Intermediate,
+                    Some("expert") => Preset::Expert,
+                    Some(level) => level
+                        .parse()
+                        .ok()
+                        .filter(|level| {
+                            (GameDifficulty::MIN_LEVEL..=GameDifficulty::MAX_LEVEL).contains(level)
+                        })
+                        .map(Preset::Level)
+                        .ok_or_else(|| format!("Unknown difficulty: {level}"))?,
+                    None => return Err("Missing difficulty".to_string()),
+                };
+                options.difficulty = preset.difficulty().map_err(|err| err.to_string())?;
+            }
+            "--seed" => {
+                let seed = args.next().ok_or("Missing seed")?;
+                options.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("The seed must be a whole number: {seed}"))?,
+                );
+            }
+            _ => return Err(format!("Unknown argument: {arg}")),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_command(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let mut position = || -> Result<CellPosition, String> {
+        let mut coordinate = || {
+            words
+                .next()
+                .and_then(|word| word.parse().ok())
+                .ok_or_else(|| format!("{name} needs a column and a row"))
+        };
+        Ok(CellPosition::new(coordinate()?, coordinate()?))
+    };
+
+    match name {
+        "reveal" => position().map(Command::Reveal),
+        "flag" => position().map(Command::Flag),
+        "chord" => position().map(Command::Chord),
+        "print" => Ok(Command::Print),
+        "help" => Ok(Command::Help),
+        "quit" => Ok(Command::Quit),
+        _ => Err(format!("Unknown command: {name}, try help")),
+    }
+}
+
+/// Plays a move. Returns why nothing happened if the move was refused.
+fn play(game_state: &mut GameState, command: &Command) -> GameResult<Option<&'static str>> {
+    let reveal_result = match *command {
+        Command::Reveal(pos) => game_state.reveal_cell(pos)?,
+        Command::Chord(pos) => game_state.chording(pos)?,
+        Command::Flag(pos) => {
+            return Ok(match game_state.toggle_flag(pos)? {
+                FlagResult::Flagged | FlagResult::Unflagged | FlagResult::Questioned => None,
+                FlagResult::AlreadyRevealed => Some("the cell is revealed"),
+                FlagResult::GameOver => Some("the game is over"),
+            });
+        }
+        Command::Print | Command::Help | Command::Quit => return Ok(None),
+    };
+
+    Ok(match reveal_result {
+        RevealResult::Continue(_) | RevealResult::GameOver(_) => None,
+        RevealResult::CantReveal(CantReveal::AlreadyRevealed) => Some("the cell is revealed"),
+        RevealResult::CantReveal(CantReveal::Flagged) => Some("the cell is flagged"),
+        RevealResult::CantReveal(CantReveal::NothingToReveal) => Some("nothing to reveal"),
+        RevealResult::CantReveal(CantReveal::UnsafeChord) => {
+            Some("the chord relies on an unproven flag")
+        }
+    })
+}
+
+fn describe_status(game_state: &GameState) -> String {
+    match game_state.status() {
+        GameStatus::Won => format!("won in {}s", game_state.elapsed_seconds()),
+        GameStatus::Lost => "lost".to_string(),
+        GameStatus::InProgress | GameStatus::New => {
+            let (width, height) = game_state.difficulty().board_size;
+            format!(
+                "playing {width}x{height}, {} mines left",
+                game_state.flags_remaining()
+            )
+        }
+    }
+}
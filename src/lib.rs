@@ -1,4 +1,8 @@
 pub mod app;
+pub mod audio;
 pub mod components;
 pub mod error;
 pub mod game;
+pub mod headless;
+pub mod settings;
+pub mod theme;
@@ -1,11 +1,20 @@
 use relm4::RelmApp;
-use rusty_minesweeper::app::App;
-use rusty_minesweeper::game::models::game::GameDifficulty;
+use rusty_minesweeper::app::{settings_path, App};
+use rusty_minesweeper::headless::{self, HEADLESS_FLAG};
+use rusty_minesweeper::settings::Settings;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == HEADLESS_FLAG) {
+        return headless::main(&args);
+    }
 
-fn main() {
     let app = RelmApp::new("not4rt.rusty-minesweeper");
-    let difficulty = GameDifficulty::default();
+    let difficulty = Settings::load(&settings_path()).start_difficulty();
 
     relm4::set_global_css(include_str!("css/style.css"));
     app.run::<App>(difficulty);
+
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,61 @@
+use crate::error::GameResult;
+use crate::game::models::board::Board;
+use crate::game::models::game::GameDifficulty;
+use crate::game::scores::{load_json, save_json};
+use crate::theme::{NumberPalette, Theme};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Preferences kept from one launch to the next. Settings missing from the
+/// file take their default, so older files still load.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+    pub number_palette: NumberPalette,
+    /// Cell size, in pixels, the player picked or zoomed to. `None` fits
+    /// the cells to the board.
+    pub cell_size: Option<i32>,
+    /// Sum up each game in a dialog as it ends. On by default.
+    pub result_dialog: bool,
+    /// Difficulty last picked, played again on the next launch.
+    pub difficulty: Option<GameDifficulty>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            number_palette: NumberPalette::default(),
+            cell_size: None,
+            result_dialog: true,
+            difficulty: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings saved at `path`. A missing or unreadable file
+    /// gives the default settings.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        load_json(path)
+    }
+
+    /// Returns the difficulty to start with: the one last picked, or the
+    /// default one if none was or no board can be built with it.
+    #[must_use]
+    pub fn start_difficulty(&self) -> GameDifficulty {
+        self.difficulty
+            .filter(|difficulty| Board::validate_difficulty(*difficulty).is_ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the settings to `path`, creating its directory if needed.
+    ///
+    /// # Errors
+    /// Will return `GameError` if the file can't be written.
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        save_json(self, path)
+    }
+}
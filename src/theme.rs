@@ -0,0 +1,159 @@
+use gtk::gdk;
+use serde::{Deserialize, Serialize};
+
+const CLASSIC_CSS: &str = include_str!("css/themes/classic.css");
+const DARK_CSS: &str = include_str!("css/themes/dark.css");
+const HIGH_CONTRAST_CSS: &str = include_str!("css/themes/high-contrast.css");
+// CSS classes of the numbers 1 to 8, as the cells set them.
+const NUMBER_CLASSES: [&str; 8] = [
+    "number-one",
+    "number-two",
+    "number-three",
+    "number-four",
+    "number-five",
+    "number-six",
+    "number-seven",
+    "number-eight",
+];
+// The colors of 1 to 8 of each palette, for light and dark cells. They keep
+// apart in lightness as well as hue, and are built around the blue and
+// orange the eye still tells apart without red or green cones.
+const DEUTERANOPIA_LIGHT: [&str; 8] = [
+    "#0060b0", "#a05a00", "#b0206e", "#1b2a80", "#6a3d00", "#005f73", "#000000", "#5a5a5a",
+];
+const DEUTERANOPIA_DARK: [&str; 8] = [
+    "#56b4e9", "#e69f00", "#cc79a7", "#9aa8ff", "#d9a066", "#2ec4b6", "#ffffff", "#a0a0a0",
+];
+// Red looks darker without red cones, so none of them lean on it.
+const PROTANOPIA_LIGHT: [&str; 8] = [
+    "#0060b0", "#806400", "#7b2cbf", "#1b2a80", "#a65e00", "#006d77", "#000000", "#5a5a5a",
+];
+const PROTANOPIA_DARK: [&str; 8] = [
+    "#56b4e9", "#f0e442", "#c8a2ff", "#9aa8ff", "#e69f00", "#2ec4b6", "#ffffff", "#a0a0a0",
+];
+
+/// The look of the game. Each theme has a stylesheet setting the colors the
+/// main one uses, the number colors included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    /// The gray look of Windows 95.
+    #[default]
+    Classic,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Self; 3] = [Self::Classic, Self::Dark, Self::HighContrast];
+
+    #[must_use]
+    pub const fn css(self) -> &'static str {
+        match self {
+            Self::Classic => CLASSIC_CSS,
+            Self::Dark => DARK_CSS,
+            Self::HighContrast => HIGH_CONTRAST_CSS,
+        }
+    }
+
+    /// Returns the number the Theme menu knows the theme by.
+    #[must_use]
+    pub const fn id(self) -> u8 {
+        self as u8
+    }
+
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(id)).copied()
+    }
+
+    /// Returns whether revealed cells are dark, so numbers need light colors.
+    #[must_use]
+    pub const fn is_dark(self) -> bool {
+        matches!(self, Self::Dark | Self::HighContrast)
+    }
+}
+
+/// The colors of the numbers. Colorblind palettes replace the colors of the
+/// theme with ones people with that color blindness tell apart, and the
+/// numbers get their symbols too, so they never rely on color alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NumberPalette {
+    /// The colors of the theme.
+    #[default]
+    Standard,
+    /// For green-blind players, the most common color blindness.
+    Deuteranopia,
+    /// For red-blind players.
+    Protanopia,
+}
+
+impl NumberPalette {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::Deuteranopia, Self::Protanopia];
+
+    /// Returns the number the Colorblind Numbers menu knows the palette by.
+    #[must_use]
+    pub const fn id(self) -> u8 {
+        self as u8
+    }
+
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(id)).copied()
+    }
+
+    /// Returns whether the palette is made for a color blindness.
+    #[must_use]
+    pub const fn is_colorblind(self) -> bool {
+        !matches!(self, Self::Standard)
+    }
+
+    /// Returns the stylesheet setting the number colors on the cells of
+    /// `theme`, empty for the colors of the theme.
+    #[must_use]
+    pub fn css(self, theme: Theme) -> String {
+        let colors = match (self, theme.is_dark()) {
+            (Self::Standard, _) => return String::new(),
+            (Self::Deuteranopia, false) => DEUTERANOPIA_LIGHT,
+            (Self::Deuteranopia, true) => DEUTERANOPIA_DARK,
+            (Self::Protanopia, false) => PROTANOPIA_LIGHT,
+            (Self::Protanopia, true) => PROTANOPIA_DARK,
+        };
+
+        NUMBER_CLASSES
+            .iter()
+            .zip(colors)
+            .map(|(class, color)| format!(".{class} {{\n    color: {color};\n}}\n"))
+            .collect()
+    }
+}
+
+/// Holds the stylesheet of the current theme and number palette, right above
+/// the main stylesheet, so both can be switched while the game runs.
+pub struct ThemeProvider {
+    provider: gtk::CssProvider,
+}
+
+impl ThemeProvider {
+    #[must_use]
+    pub fn new(theme: Theme, palette: NumberPalette) -> Self {
+        let provider = gtk::CssProvider::new();
+        if let Some(display) = gdk::Display::default() {
+            gtk::style_context_add_provider_for_display(
+                &display,
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+            );
+        }
+
+        let theme_provider = Self { provider };
+        theme_provider.apply(theme, palette);
+        theme_provider
+    }
+
+    /// Replaces the stylesheet with the one of `theme`, its number colors
+    /// overridden by `palette`.
+    pub fn apply(&self, theme: Theme, palette: NumberPalette) {
+        let css = format!("{}\n{}", theme.css(), palette.css(theme));
+        self.provider.load_from_string(&css);
+    }
+}